@@ -8,16 +8,22 @@ pub const DOUBLE_FAULT_STACK_INDEX: u16 = 0;
 const KERNEL_STACK_SIZE: usize = 4096 * 5;
 const DOUBLE_FAULT_STACK_SIZE: usize = 4096 * 5;
 
+// The CPU expects 16-byte aligned stacks
+#[repr(align(16))]
+struct Stack<const SIZE: usize>([u8; SIZE]);
+
 static TASK_STATE_SEGMENT: Lazy<TaskStateSegment> = Lazy::new(|| {
     let mut tss = TaskStateSegment::new();
     
-    static KERNEL_STACK: [u8; KERNEL_STACK_SIZE] = [0; KERNEL_STACK_SIZE];
-    let kernel_stack_bottom = VirtAddr::from_ptr(&raw const KERNEL_STACK);
+    // The stacks must be `static mut`: an immutable static can be placed in
+    // read-only memory, and the first push onto it would fault again
+    static mut KERNEL_STACK: Stack<KERNEL_STACK_SIZE> = Stack([0; KERNEL_STACK_SIZE]);
+    let kernel_stack_bottom = VirtAddr::from_ptr(&raw mut KERNEL_STACK);
     let kernel_stack_top = kernel_stack_bottom + KERNEL_STACK_SIZE;
     tss.privilege_stack_table[0] = kernel_stack_top;
     
-    static DOUBLE_FAULT_STACK: [u8; DOUBLE_FAULT_STACK_SIZE] = [0; DOUBLE_FAULT_STACK_SIZE];
-    let double_fault_stack_bottom = VirtAddr::from_ptr(&raw const DOUBLE_FAULT_STACK);
+    static mut DOUBLE_FAULT_STACK: Stack<DOUBLE_FAULT_STACK_SIZE> = Stack([0; DOUBLE_FAULT_STACK_SIZE]);
+    let double_fault_stack_bottom = VirtAddr::from_ptr(&raw mut DOUBLE_FAULT_STACK);
     let double_fault_stack_top = double_fault_stack_bottom + DOUBLE_FAULT_STACK_SIZE;
     tss.interrupt_stack_table[DOUBLE_FAULT_STACK_INDEX as usize] = double_fault_stack_top;
     
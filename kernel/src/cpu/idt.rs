@@ -1,13 +1,20 @@
-use x86_64::structures::idt::{InterruptDescriptorTable, InterruptStackFrame};
+use core::fmt;
+use x86_64::structures::idt::{InterruptDescriptorTable, InterruptStackFrame, InterruptStackFrameValue, PageFaultErrorCode};
+use x86_64::registers::control::Cr2;
 use spin::Lazy;
 use super::gdt;
-use crate::drivers::{pic, keyboard};
+use crate::drivers::{pic, keyboard, apic};
 
-const TIMER_INTERRUPT_VECTOR: usize = 32;
+pub const TIMER_INTERRUPT_VECTOR: usize = 32;
 const KEYBOARD_INTERRUPT_VECTOR: usize = 33;
 
 static INTERRUPT_DESCRIPTOR_TABLE: Lazy<InterruptDescriptorTable> = Lazy::new(|| {
     let mut idt = InterruptDescriptorTable::new();
+
+    idt.divide_error.set_handler_fn(handle_divide_error);
+    idt.invalid_opcode.set_handler_fn(handle_invalid_opcode);
+    idt.general_protection_fault.set_handler_fn(handle_general_protection_fault);
+    idt.page_fault.set_handler_fn(handle_page_fault);
     
     unsafe {
         idt.double_fault
@@ -17,25 +24,133 @@ static INTERRUPT_DESCRIPTOR_TABLE: Lazy<InterruptDescriptorTable> = Lazy::new(||
 
     idt[TIMER_INTERRUPT_VECTOR].set_handler_fn(handle_timer_interrupt);
     idt[KEYBOARD_INTERRUPT_VECTOR].set_handler_fn(handle_keyboard_interrupt);
+    idt[apic::SPURIOUS_INTERRUPT_VECTOR as usize].set_handler_fn(handle_spurious_interrupt);
 
     idt
 });
 
+// CPU exceptions panic with the faulting state, which the panic handler shows
+// on screen and writes to the serial port
+
+// What a handler knows about the fault beyond the saved stack frame
+enum FaultDetails {
+    None,
+    ErrorCode(u64),
+    PageFault { address: u64, error_code: PageFaultErrorCode },
+}
+
+// The exception name and details, then the registers the CPU saved on entry.
+// General purpose registers are not saved by x86-interrupt handlers.
+struct FaultReport<'a> {
+    exception: &'static str,
+    details: FaultDetails,
+    frame: &'a InterruptStackFrameValue,
+}
+
+impl fmt::Display for FaultReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.exception)?;
+        match self.details {
+            FaultDetails::None => {}
+            FaultDetails::ErrorCode(error_code) => write!(f, " (error code: {:#x})", error_code)?,
+            FaultDetails::PageFault { address, error_code } => write!(f, " at {:#x} ({:?})", address, error_code)?,
+        }
+
+        let frame = self.frame;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "RIP: {:#018x}  CS: {:#06x}  RFLAGS: {:#010x}",
+            frame.instruction_pointer.as_u64(),
+            frame.code_segment,
+            frame.cpu_flags,
+        )?;
+        write!(f, "RSP: {:#018x}  SS: {:#06x}", frame.stack_pointer.as_u64(), frame.stack_segment)
+    }
+}
+
+fn fault(exception: &'static str, details: FaultDetails, frame: &InterruptStackFrameValue) -> ! {
+    panic!("{}", FaultReport { exception, details, frame });
+}
+
+extern "x86-interrupt" fn handle_divide_error(stack_frame: InterruptStackFrame) {
+    fault("DIVIDE ERROR", FaultDetails::None, &stack_frame);
+}
+
+extern "x86-interrupt" fn handle_invalid_opcode(stack_frame: InterruptStackFrame) {
+    fault("INVALID OPCODE", FaultDetails::None, &stack_frame);
+}
+
+extern "x86-interrupt" fn handle_general_protection_fault(stack_frame: InterruptStackFrame, error_code: u64) {
+    // A non-zero error code is the selector that caused the fault
+    fault("GENERAL PROTECTION FAULT", FaultDetails::ErrorCode(error_code), &stack_frame);
+}
+
+extern "x86-interrupt" fn handle_page_fault(stack_frame: InterruptStackFrame, error_code: PageFaultErrorCode) {
+    // CR2 holds the address whose access faulted
+    let address = Cr2::read().as_u64();
+    fault("PAGE FAULT", FaultDetails::PageFault { address, error_code }, &stack_frame);
+}
+
 extern "x86-interrupt" fn handle_double_fault(stack_frame: InterruptStackFrame, error_code: u64) -> ! {
-    panic!("DOUBLE FAULT (error code: {})\n{:#?}", error_code, stack_frame);
+    fault("DOUBLE FAULT", FaultDetails::ErrorCode(error_code), &stack_frame);
 }
 
 extern "x86-interrupt" fn handle_timer_interrupt(_stack_frame: InterruptStackFrame) {
     crate::drivers::timer::tick();
-    pic::send_eoi(0);
+    keyboard::repeat_held_key(crate::drivers::timer::ticks());
+    crate::drivers::timer::send_eoi();
 }
 
 extern "x86-interrupt" fn handle_keyboard_interrupt(_stack_frame: InterruptStackFrame) {
-    let scancode = keyboard::read_scancode();
-    keyboard::handle_scancode(scancode);
+    keyboard::handle_interrupt();
     pic::send_eoi(1);
 }
 
+// Spurious APIC interrupts must not be acknowledged
+extern "x86-interrupt" fn handle_spurious_interrupt(_stack_frame: InterruptStackFrame) {}
+
 pub fn init() {
     INTERRUPT_DESCRIPTOR_TABLE.load();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use x86_64::VirtAddr;
+
+    const FRAME: InterruptStackFrameValue = InterruptStackFrameValue {
+        instruction_pointer: VirtAddr::new_truncate(0x20_1a3c),
+        code_segment: 0x08,
+        cpu_flags: 0x246,
+        stack_pointer: VirtAddr::new_truncate(0x1000_0ff8),
+        stack_segment: 0,
+    };
+
+    #[test]
+    fn page_fault_report_has_the_address_cause_and_registers() {
+        let details = FaultDetails::PageFault {
+            address: 0xdead_b000,
+            error_code: PageFaultErrorCode::CAUSED_BY_WRITE,
+        };
+        let report = format!("{}", FaultReport { exception: "PAGE FAULT", details, frame: &FRAME });
+
+        let mut lines = report.lines();
+        let title = lines.next().unwrap();
+        assert!(title.starts_with("PAGE FAULT at 0xdeadb000 ("), "{title}");
+        assert!(title.contains("CAUSED_BY_WRITE"), "{title}");
+        assert_eq!(lines.next(), Some("RIP: 0x0000000000201a3c  CS: 0x0008  RFLAGS: 0x00000246"));
+        assert_eq!(lines.next(), Some("RSP: 0x0000000010000ff8  SS: 0x0000"));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn error_code_is_shown_in_hex() {
+        let report = FaultReport { exception: "GENERAL PROTECTION FAULT", details: FaultDetails::ErrorCode(0x18), frame: &FRAME };
+        assert!(format!("{}", report).starts_with("GENERAL PROTECTION FAULT (error code: 0x18)\nRIP: "));
+
+        let report = FaultReport { exception: "DIVIDE ERROR", details: FaultDetails::None, frame: &FRAME };
+        assert!(format!("{}", report).starts_with("DIVIDE ERROR\nRIP: "));
+    }
+}
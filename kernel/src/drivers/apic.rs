@@ -1,36 +1,159 @@
+use spin::Once;
+
 const APIC_BASE_MODEL_SPECIFIC_REGISTER: u32 = 0x1B;
-const APIC_ENABLED_BIT: u64 = 1 << 11;
+const APIC_BASE_ADDRESS_MASK: u64 = 0x000F_FFFF_FFFF_F000;
+
+const CPUID_FEATURES_LEAF: u32 = 1;
+const CPUID_EDX_APIC_PRESENT: u32 = 1 << 9;
+
+const REGISTER_END_OF_INTERRUPT: usize = 0xB0;
+const REGISTER_SPURIOUS_INTERRUPT_VECTOR: usize = 0xF0;
+const REGISTER_LVT_TIMER: usize = 0x320;
+const REGISTER_LVT_LINT0: usize = 0x350;
+const REGISTER_TIMER_INITIAL_COUNT: usize = 0x380;
+const REGISTER_TIMER_CURRENT_COUNT: usize = 0x390;
+const REGISTER_TIMER_DIVIDE_CONFIGURATION: usize = 0x3E0;
+
+pub const SPURIOUS_INTERRUPT_VECTOR: u8 = 0xFF;
+const SOFTWARE_ENABLE_BIT: u32 = 1 << 8;
+const LVT_MASKED: u32 = 1 << 16;
+const LVT_TIMER_PERIODIC: u32 = 1 << 17;
+const LVT_DELIVERY_MODE_EXTINT: u32 = 0b111 << 8;
+const TIMER_DIVIDE_BY_16: u32 = 0x3;
+
+static PHYSICAL_MEMORY_OFFSET: Once<u64> = Once::new();
+// Virtual address of the register page, read from IA32_APIC_BASE once so
+// register accesses (EOI in particular) do not pay for an rdmsr each time
+static REGISTER_BASE: Once<u64> = Once::new();
+
+pub fn init(physical_memory_offset: Option<u64>) {
+    if let Some(offset) = physical_memory_offset {
+        PHYSICAL_MEMORY_OFFSET.call_once(|| offset);
+    }
+}
+
+// Quiets the local APIC without clearing the global enable bit in
+// IA32_APIC_BASE, which cannot be set again without a reset. Only the timer
+// is masked; LINT0 keeps delivering the 8259 PIC interrupts as ExtINT.
+pub fn enter_pic_mode() {
+    if !is_available() {
+        return;
+    }
+    map_registers();
 
-pub fn disable() {
     unsafe {
-        let current_value = read_model_specific_register(APIC_BASE_MODEL_SPECIFIC_REGISTER);
-        let value_with_apic_disabled = current_value & !APIC_ENABLED_BIT;
-        write_model_specific_register(APIC_BASE_MODEL_SPECIFIC_REGISTER, value_with_apic_disabled);
+        // LVT writes are forced masked while the APIC is software-disabled,
+        // so the enable bit has to be set before LINT0 is programmed
+        write_register(REGISTER_SPURIOUS_INTERRUPT_VECTOR, SOFTWARE_ENABLE_BIT | SPURIOUS_INTERRUPT_VECTOR as u32);
+        write_register(REGISTER_LVT_TIMER, LVT_MASKED);
+        write_register(REGISTER_TIMER_INITIAL_COUNT, 0);
+        write_register(REGISTER_LVT_LINT0, LVT_DELIVERY_MODE_EXTINT);
     }
 }
 
-unsafe fn read_model_specific_register(register: u32) -> u64 {
-    let (low, high): (u32, u32);
+pub fn is_available() -> bool {
+    PHYSICAL_MEMORY_OFFSET.get().is_some() && cpuid_features_edx() & CPUID_EDX_APIC_PRESENT != 0
+}
+
+pub fn enable() -> Result<(), &'static str> {
+    if !is_available() {
+        return Err("local APIC not available");
+    }
+    map_registers();
+
+    unsafe {
+        write_register(REGISTER_SPURIOUS_INTERRUPT_VECTOR, SOFTWARE_ENABLE_BIT | SPURIOUS_INTERRUPT_VECTOR as u32);
+    }
+
+    Ok(())
+}
+
+// Starts a masked one-shot countdown from the maximum count, used to measure
+// the APIC timer rate against a known clock
+pub fn start_calibration_countdown() {
+    unsafe {
+        write_register(REGISTER_TIMER_DIVIDE_CONFIGURATION, TIMER_DIVIDE_BY_16);
+        write_register(REGISTER_LVT_TIMER, LVT_MASKED);
+        write_register(REGISTER_TIMER_INITIAL_COUNT, u32::MAX);
+    }
+}
+
+// Stops the calibration countdown and returns how many APIC ticks elapsed
+pub fn stop_calibration_countdown() -> u32 {
+    unsafe {
+        let remaining = read_register(REGISTER_TIMER_CURRENT_COUNT);
+        write_register(REGISTER_TIMER_INITIAL_COUNT, 0);
+        u32::MAX - remaining
+    }
+}
+
+pub fn start_periodic_timer(vector: u8, initial_count: u32) {
+    unsafe {
+        write_register(REGISTER_TIMER_DIVIDE_CONFIGURATION, TIMER_DIVIDE_BY_16);
+        write_register(REGISTER_LVT_TIMER, vector as u32 | LVT_TIMER_PERIODIC);
+        write_register(REGISTER_TIMER_INITIAL_COUNT, initial_count);
+    }
+}
+
+pub fn stop_timer() {
+    unsafe {
+        write_register(REGISTER_LVT_TIMER, LVT_MASKED);
+        write_register(REGISTER_TIMER_INITIAL_COUNT, 0);
+    }
+}
+
+pub fn send_eoi() {
+    unsafe {
+        write_register(REGISTER_END_OF_INTERRUPT, 0);
+    }
+}
+
+fn map_registers() {
+    REGISTER_BASE.call_once(|| {
+        let offset = PHYSICAL_MEMORY_OFFSET.get().copied().unwrap_or(0);
+        let physical_base = unsafe { read_model_specific_register(APIC_BASE_MODEL_SPECIFIC_REGISTER) } & APIC_BASE_ADDRESS_MASK;
+        offset + physical_base
+    });
+}
+
+fn register_address(register: usize) -> *mut u32 {
+    let base = REGISTER_BASE.get().copied().expect("local APIC registers used before enable");
+    (base + register as u64) as *mut u32
+}
+
+unsafe fn read_register(register: usize) -> u32 {
+    unsafe { core::ptr::read_volatile(register_address(register)) }
+}
+
+unsafe fn write_register(register: usize, value: u32) {
+    unsafe { core::ptr::write_volatile(register_address(register), value) }
+}
+
+fn cpuid_features_edx() -> u32 {
+    let edx: u32;
     unsafe {
+        // rbx is reserved by LLVM, so preserve it around cpuid
         core::arch::asm!(
-            "rdmsr",
-            in("ecx") register,
-            out("eax") low,
-            out("edx") high,
+            "push rbx",
+            "cpuid",
+            "pop rbx",
+            inout("eax") CPUID_FEATURES_LEAF => _,
+            inout("ecx") 0u32 => _,
+            out("edx") edx,
         );
     }
-    ((high as u64) << 32) | (low as u64)
+    edx
 }
 
-unsafe fn write_model_specific_register(register: u32, value: u64) {
-    let low = value as u32;
-    let high = (value >> 32) as u32;
+unsafe fn read_model_specific_register(register: u32) -> u64 {
+    let (low, high): (u32, u32);
     unsafe {
         core::arch::asm!(
-            "wrmsr",
+            "rdmsr",
             in("ecx") register,
-            in("eax") low,
-            in("edx") high,
+            out("eax") low,
+            out("edx") high,
         );
     }
+    ((high as u64) << 32) | (low as u64)
 }
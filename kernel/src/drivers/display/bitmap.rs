@@ -1,7 +1,216 @@
 use alloc::vec::Vec;
 
+const BMP_MAGIC: &[u8; 2] = b"BM";
+const BMP_FILE_HEADER_SIZE: usize = 14;
+const BMP_INFO_HEADER_MIN_SIZE: usize = 40;
+const BMP_MAX_DIMENSION: usize = 4096;
+
+const BMP_COMPRESSION_NONE: u32 = 0;
+const BMP_COMPRESSION_BITFIELDS: u32 = 3;
+
 pub struct Bitmap {
     pub width: usize,
     pub height: usize,
     pub pixels: Vec<u32>,
 }
+
+impl Bitmap {
+    // Decodes an uncompressed 24 or 32 bit BMP into 0xAARRGGBB pixels
+    pub fn from_bmp(data: &[u8]) -> Result<Bitmap, &'static str> {
+        if data.len() < BMP_FILE_HEADER_SIZE + BMP_INFO_HEADER_MIN_SIZE || &data[0..2] != BMP_MAGIC {
+            return Err("not a BMP file");
+        }
+
+        let pixel_data_offset = read_u32(data, 10)? as usize;
+        let info_header_size = read_u32(data, 14)? as usize;
+        let raw_width = read_i32(data, 18)?;
+        let raw_height = read_i32(data, 22)?;
+        let bits_per_pixel = read_u16(data, 28)?;
+        let compression = read_u32(data, 30)?;
+
+        if info_header_size < BMP_INFO_HEADER_MIN_SIZE {
+            return Err("unsupported BMP header");
+        }
+        if bits_per_pixel != 24 && bits_per_pixel != 32 {
+            return Err("only 24 and 32 bit BMPs are supported");
+        }
+
+        let masks = match (compression, bits_per_pixel) {
+            (BMP_COMPRESSION_NONE, _) => None,
+            (BMP_COMPRESSION_BITFIELDS, 32) => Some(ChannelMasks {
+                red: read_u32(data, 54)?,
+                green: read_u32(data, 58)?,
+                blue: read_u32(data, 62)?,
+                alpha: if info_header_size >= 56 { read_u32(data, 66)? } else { 0 },
+            }),
+            _ => return Err("compressed BMPs are not supported"),
+        };
+
+        // Positive heights are stored bottom-up, negative heights top-down
+        let top_down = raw_height < 0;
+        let width = raw_width.unsigned_abs() as usize;
+        let height = raw_height.unsigned_abs() as usize;
+        if raw_width <= 0 || height == 0 || width > BMP_MAX_DIMENSION || height > BMP_MAX_DIMENSION {
+            return Err("invalid BMP dimensions");
+        }
+
+        let bytes_per_pixel = bits_per_pixel as usize / 8;
+        // Every row is padded to a multiple of 4 bytes
+        let row_size = (width * bytes_per_pixel).div_ceil(4) * 4;
+        let pixel_data_end = pixel_data_offset + row_size * height;
+        if pixel_data_end > data.len() {
+            return Err("truncated BMP pixel data");
+        }
+
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in 0..height {
+            let source_row = if top_down { row } else { height - 1 - row };
+            let row_start = pixel_data_offset + source_row * row_size;
+
+            for col in 0..width {
+                let pixel = &data[row_start + col * bytes_per_pixel..][..bytes_per_pixel];
+                pixels.push(decode_pixel(pixel, masks.as_ref()));
+            }
+        }
+
+        Ok(Bitmap { width, height, pixels })
+    }
+}
+
+struct ChannelMasks {
+    red: u32,
+    green: u32,
+    blue: u32,
+    alpha: u32,
+}
+
+fn decode_pixel(pixel: &[u8], masks: Option<&ChannelMasks>) -> u32 {
+    match (pixel, masks) {
+        (&[blue, green, red], _) | (&[blue, green, red, _], None) => {
+            0xFF000000 | (red as u32) << 16 | (green as u32) << 8 | blue as u32
+        }
+        (&[b0, b1, b2, b3], Some(masks)) => {
+            let value = u32::from_le_bytes([b0, b1, b2, b3]);
+            let alpha = if masks.alpha == 0 { 0xFF } else { extract_channel(value, masks.alpha) };
+            alpha << 24
+                | extract_channel(value, masks.red) << 16
+                | extract_channel(value, masks.green) << 8
+                | extract_channel(value, masks.blue)
+        }
+        _ => 0,
+    }
+}
+
+fn extract_channel(value: u32, mask: u32) -> u32 {
+    if mask == 0 {
+        return 0;
+    }
+
+    let channel = (value & mask) >> mask.trailing_zeros();
+    let channel_bits = (mask >> mask.trailing_zeros()).count_ones();
+    // Scale channels narrower or wider than 8 bits to 0..=255
+    if channel_bits >= 8 {
+        channel >> (channel_bits - 8)
+    } else {
+        channel * 0xFF / ((1 << channel_bits) - 1)
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, &'static str> {
+    let bytes = data.get(offset..offset + 2).ok_or("truncated BMP header")?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, &'static str> {
+    let bytes = data.get(offset..offset + 4).ok_or("truncated BMP header")?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_i32(data: &[u8], offset: usize) -> Result<i32, &'static str> {
+    read_u32(data, offset).map(|value| value as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BGR_RED: [u8; 3] = [0x00, 0x00, 0xFF];
+    const BGR_GREEN: [u8; 3] = [0x00, 0xFF, 0x00];
+    const BGR_BLUE: [u8; 3] = [0xFF, 0x00, 0x00];
+    const BGR_WHITE: [u8; 3] = [0xFF, 0xFF, 0xFF];
+    const ROW_PADDING: [u8; 2] = [0, 0];
+
+    fn bmp_file(width: i32, height: i32, bits_per_pixel: u16, compression: u32, masks: &[u32], pixel_data: &[u8]) -> Vec<u8> {
+        let info_header_size = BMP_INFO_HEADER_MIN_SIZE + masks.len() * 4;
+        let pixel_data_offset = BMP_FILE_HEADER_SIZE + info_header_size;
+
+        let mut data = Vec::new();
+        data.extend_from_slice(BMP_MAGIC);
+        data.extend_from_slice(&((pixel_data_offset + pixel_data.len()) as u32).to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(pixel_data_offset as u32).to_le_bytes());
+        data.extend_from_slice(&(info_header_size as u32).to_le_bytes());
+        data.extend_from_slice(&width.to_le_bytes());
+        data.extend_from_slice(&height.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&bits_per_pixel.to_le_bytes());
+        data.extend_from_slice(&compression.to_le_bytes());
+        // Image size, resolution and palette counts are not read
+        data.extend_from_slice(&[0; 20]);
+        for mask in masks {
+            data.extend_from_slice(&mask.to_le_bytes());
+        }
+        data.extend_from_slice(pixel_data);
+        data
+    }
+
+    fn bottom_up_24_bit_square() -> Vec<u8> {
+        // Rows of 6 bytes are padded to 8, and the bottom row comes first
+        let pixel_data = [BGR_BLUE, BGR_WHITE]
+            .as_flattened()
+            .iter()
+            .chain(&ROW_PADDING)
+            .chain([BGR_RED, BGR_GREEN].as_flattened())
+            .chain(&ROW_PADDING)
+            .copied()
+            .collect::<Vec<u8>>();
+        bmp_file(2, 2, 24, BMP_COMPRESSION_NONE, &[], &pixel_data)
+    }
+
+    #[test]
+    fn decodes_bottom_up_24_bit_rows_with_padding() {
+        let bitmap = Bitmap::from_bmp(&bottom_up_24_bit_square()).unwrap();
+
+        assert_eq!((bitmap.width, bitmap.height), (2, 2));
+        assert_eq!(bitmap.pixels, [0xFFFF0000, 0xFF00FF00, 0xFF0000FF, 0xFFFFFFFF]);
+    }
+
+    #[test]
+    fn decodes_32_bit_bitfields_with_alpha() {
+        // Channels stored as RGBA from the most significant byte down, top-down
+        let masks = [0xFF000000, 0x00FF0000, 0x0000FF00, 0x000000FF];
+        let pixel_data = [0x11223380u32, 0xAABBCCFF].map(u32::to_le_bytes);
+        let data = bmp_file(2, -1, 32, BMP_COMPRESSION_BITFIELDS, &masks, pixel_data.as_flattened());
+
+        let bitmap = Bitmap::from_bmp(&data).unwrap();
+        assert_eq!(bitmap.pixels, [0x80112233, 0xFFAABBCC]);
+    }
+
+    #[test]
+    fn rejects_truncated_headers_and_pixel_data() {
+        let data = bottom_up_24_bit_square();
+
+        assert!(Bitmap::from_bmp(&data[..30]).is_err());
+        assert!(Bitmap::from_bmp(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_oversized_dimensions() {
+        let mut data = bottom_up_24_bit_square();
+        data[0] = b'X';
+        assert!(Bitmap::from_bmp(&data).is_err());
+
+        let oversized = bmp_file(BMP_MAX_DIMENSION as i32 + 1, 1, 24, BMP_COMPRESSION_NONE, &[], &[]);
+        assert_eq!(Bitmap::from_bmp(&oversized).err(), Some("invalid BMP dimensions"));
+    }
+}
@@ -15,12 +15,95 @@ pub fn color_to_bytes(hex: u32, format: PixelFormat) -> Option<[u8; 4]> {
             let gray = ((red as u16 + green as u16 + blue as u16) / 3) as u8;
             Some([gray, 0, 0, alpha])
         },
+        // The positions are bit offsets of each channel within the pixel, so the
+        // channels are packed into a little-endian pixel value
         PixelFormat::Unknown { red_position, green_position, blue_position } => {
-            let r = (hex >> red_position) as u8;
-            let g = (hex >> green_position) as u8;
-            let b = (hex >> blue_position) as u8;
-            Some([r, g, b, alpha])
+            let value = shift_channel(red, red_position)
+                | shift_channel(green, green_position)
+                | shift_channel(blue, blue_position);
+            let [b0, b1, b2, _] = value.to_le_bytes();
+            Some([b0, b1, b2, alpha])
         },
         _ => Some([red, green, blue, alpha]),
     }
 }
+
+pub fn bytes_to_color(bytes: &[u8], format: PixelFormat) -> Option<u32> {
+    let (red, green, blue) = match format {
+        PixelFormat::Bgr => {
+            let [blue, green, red] = *bytes.get(..3)? else { return None };
+            (red, green, blue)
+        },
+        PixelFormat::U8 => {
+            let gray = *bytes.first()?;
+            (gray, gray, gray)
+        },
+        PixelFormat::Unknown { red_position, green_position, blue_position } => {
+            let mut value = [0; 4];
+            let length = bytes.len().min(3);
+            value[..length].copy_from_slice(&bytes[..length]);
+            let value = u32::from_le_bytes(value);
+            (
+                unshift_channel(value, red_position),
+                unshift_channel(value, green_position),
+                unshift_channel(value, blue_position),
+            )
+        },
+        _ => {
+            let [red, green, blue] = *bytes.get(..3)? else { return None };
+            (red, green, blue)
+        },
+    };
+
+    Some(0xFF000000 | (red as u32) << 16 | (green as u32) << 8 | blue as u32)
+}
+
+fn shift_channel(channel: u8, position: u8) -> u32 {
+    (channel as u32).checked_shl(position as u32).unwrap_or(0)
+}
+
+fn unshift_channel(value: u32, position: u8) -> u8 {
+    value.checked_shr(position as u32).unwrap_or(0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORANGE: u32 = 0xFFFF8000;
+
+    #[test]
+    fn rgb_and_bgr_swap_red_and_blue() {
+        assert_eq!(color_to_bytes(ORANGE, PixelFormat::Rgb), Some([0xFF, 0x80, 0x00, 0xFF]));
+        assert_eq!(color_to_bytes(ORANGE, PixelFormat::Bgr), Some([0x00, 0x80, 0xFF, 0xFF]));
+    }
+
+    #[test]
+    fn u8_is_the_channel_average() {
+        assert_eq!(color_to_bytes(0xFF306090, PixelFormat::U8), Some([0x60, 0, 0, 0xFF]));
+    }
+
+    #[test]
+    fn unknown_format_places_channels_at_their_bit_positions() {
+        let bgr_bits = PixelFormat::Unknown { red_position: 16, green_position: 8, blue_position: 0 };
+        assert_eq!(color_to_bytes(ORANGE, bgr_bits), Some([0x00, 0x80, 0xFF, 0xFF]));
+
+        let swapped = PixelFormat::Unknown { red_position: 0, green_position: 8, blue_position: 16 };
+        assert_eq!(color_to_bytes(ORANGE, swapped), Some([0xFF, 0x80, 0x00, 0xFF]));
+    }
+
+    #[test]
+    fn zero_alpha_is_opaque_unless_the_color_is_black() {
+        assert_eq!(color_to_bytes(0x00123456, PixelFormat::Rgb), Some([0x12, 0x34, 0x56, 0xFF]));
+        assert_eq!(color_to_bytes(0x00000000, PixelFormat::Rgb), Some([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn bytes_round_trip_through_each_format() {
+        let unknown = PixelFormat::Unknown { red_position: 0, green_position: 8, blue_position: 16 };
+        for format in [PixelFormat::Rgb, PixelFormat::Bgr, unknown] {
+            let bytes = color_to_bytes(ORANGE, format).unwrap();
+            assert_eq!(bytes_to_color(&bytes, format), Some(ORANGE));
+        }
+    }
+}
@@ -0,0 +1,546 @@
+use super::Screen;
+use super::font::{get_char_bitmap, FONT_WIDTH, FONT_HEIGHT};
+
+#[derive(Clone, Copy)]
+pub struct ClipRect {
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl ClipRect {
+    pub fn new(x: isize, y: isize, width: usize, height: usize) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn screen(screen: &Screen) -> Self {
+        Self::new(0, 0, screen.width, screen.height)
+    }
+
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        x >= self.x
+            && y >= self.y
+            && x < self.x + self.width as isize
+            && y < self.y + self.height as isize
+    }
+
+    pub fn intersect(&self, other: &ClipRect) -> ClipRect {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.width as isize).min(other.x + other.width as isize);
+        let bottom = (self.y + self.height as isize).min(other.y + other.height as isize);
+
+        ClipRect::new(
+            left,
+            top,
+            (right - left).max(0) as usize,
+            (bottom - top).max(0) as usize,
+        )
+    }
+}
+
+pub fn draw_line(screen: &mut Screen, x0: isize, y0: isize, x1: isize, y1: isize, color: u32) {
+    let clip = ClipRect::screen(screen);
+    draw_line_clipped(screen, x0, y0, x1, y1, color, clip);
+}
+
+pub fn draw_line_clipped(
+    screen: &mut Screen,
+    x0: isize,
+    y0: isize,
+    x1: isize,
+    y1: isize,
+    color: u32,
+    clip: ClipRect,
+) {
+    let clip = clip.intersect(&ClipRect::screen(screen));
+
+    // Bresenham's algorithm with a combined error term, so it covers every
+    // octant (steep slopes and negative directions) without swapping endpoints
+    let delta_x = (x1 - x0).abs();
+    let delta_y = -(y1 - y0).abs();
+    let step_x = if x0 < x1 { 1 } else { -1 };
+    let step_y = if y0 < y1 { 1 } else { -1 };
+    let mut error = delta_x + delta_y;
+
+    let (mut x, mut y) = (x0, y0);
+    loop {
+        plot_clipped(screen, x, y, color, &clip);
+
+        if x == x1 && y == y1 {
+            break;
+        }
+
+        let doubled_error = 2 * error;
+        if doubled_error >= delta_y {
+            error += delta_y;
+            x += step_x;
+        }
+        if doubled_error <= delta_x {
+            error += delta_x;
+            y += step_y;
+        }
+    }
+}
+
+pub fn draw_filled_rect(screen: &mut Screen, x: isize, y: isize, width: usize, height: usize, color: u32) {
+    let clip = ClipRect::screen(screen);
+    draw_filled_rect_clipped(screen, x, y, width, height, color, clip);
+}
+
+pub fn draw_filled_rect_clipped(
+    screen: &mut Screen,
+    x: isize,
+    y: isize,
+    width: usize,
+    height: usize,
+    color: u32,
+    clip: ClipRect,
+) {
+    let clip = clip.intersect(&ClipRect::screen(screen));
+    if width == 0 {
+        return;
+    }
+
+    for row in y..y + height as isize {
+        fill_span_clipped(screen, x, x + width as isize - 1, row, color, &clip);
+    }
+}
+
+pub fn draw_filled_rect_alpha(screen: &mut Screen, x: isize, y: isize, width: usize, height: usize, argb: u32) {
+    let clip = ClipRect::screen(screen);
+    draw_filled_rect_alpha_clipped(screen, x, y, width, height, argb, clip);
+}
+
+pub fn draw_filled_rect_alpha_clipped(
+    screen: &mut Screen,
+    x: isize,
+    y: isize,
+    width: usize,
+    height: usize,
+    argb: u32,
+    clip: ClipRect,
+) {
+    let clip = clip.intersect(&ClipRect::screen(screen)).intersect(&ClipRect::new(x, y, width, height));
+
+    for row in clip.y..clip.y + clip.height as isize {
+        for col in clip.x..clip.x + clip.width as isize {
+            blend_pixel(screen, col as usize, row as usize, argb);
+        }
+    }
+}
+
+// Composites `argb` over the current pixel using its top byte as alpha
+// (0x00 = fully transparent, 0xFF = opaque)
+pub fn blend_pixel(screen: &mut Screen, x: usize, y: usize, argb: u32) {
+    let alpha = argb >> 24;
+    if alpha == 0 {
+        return;
+    }
+    if alpha == 0xFF {
+        screen.write_pixel(x, y, argb);
+        return;
+    }
+
+    let Some(background) = screen.read_pixel(x, y) else {
+        return;
+    };
+
+    let blend_channel = |shift: u32| {
+        let foreground = (argb >> shift) & 0xFF;
+        let background = (background >> shift) & 0xFF;
+        ((foreground * alpha + background * (0xFF - alpha) + 0x7F) / 0xFF) << shift
+    };
+
+    let blended = 0xFF000000 | blend_channel(16) | blend_channel(8) | blend_channel(0);
+    screen.write_pixel(x, y, blended);
+}
+
+pub fn draw_circle(screen: &mut Screen, center_x: isize, center_y: isize, radius: usize, color: u32) {
+    let clip = ClipRect::screen(screen);
+    draw_circle_clipped(screen, center_x, center_y, radius, color, clip);
+}
+
+pub fn draw_circle_clipped(
+    screen: &mut Screen,
+    center_x: isize,
+    center_y: isize,
+    radius: usize,
+    color: u32,
+    clip: ClipRect,
+) {
+    let clip = clip.intersect(&ClipRect::screen(screen));
+    let corners = Corners::circle(center_x, center_y);
+    stroke_corners(screen, &corners, radius as isize, color, &clip);
+}
+
+pub fn draw_filled_circle(screen: &mut Screen, center_x: isize, center_y: isize, radius: usize, color: u32) {
+    let clip = ClipRect::screen(screen);
+    draw_filled_circle_clipped(screen, center_x, center_y, radius, color, clip);
+}
+
+pub fn draw_filled_circle_clipped(
+    screen: &mut Screen,
+    center_x: isize,
+    center_y: isize,
+    radius: usize,
+    color: u32,
+    clip: ClipRect,
+) {
+    let clip = clip.intersect(&ClipRect::screen(screen));
+    let corners = Corners::circle(center_x, center_y);
+    fill_corners(screen, &corners, radius as isize, color, &clip);
+}
+
+pub fn draw_rounded_rect(
+    screen: &mut Screen,
+    x: isize,
+    y: isize,
+    width: usize,
+    height: usize,
+    radius: usize,
+    color: u32,
+) {
+    let clip = ClipRect::screen(screen);
+    draw_rounded_rect_clipped(screen, x, y, width, height, radius, color, clip);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn draw_rounded_rect_clipped(
+    screen: &mut Screen,
+    x: isize,
+    y: isize,
+    width: usize,
+    height: usize,
+    radius: usize,
+    color: u32,
+    clip: ClipRect,
+) {
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let clip = clip.intersect(&ClipRect::screen(screen));
+    let radius = clamp_corner_radius(width, height, radius);
+    let corners = Corners::rect(x, y, width, height, radius);
+    let right = x + width as isize - 1;
+    let bottom = y + height as isize - 1;
+
+    // Straight edges between the corner arcs
+    fill_span_clipped(screen, corners.left, corners.right, y, color, &clip);
+    fill_span_clipped(screen, corners.left, corners.right, bottom, color, &clip);
+    for row in corners.top..=corners.bottom {
+        plot_clipped(screen, x, row, color, &clip);
+        plot_clipped(screen, right, row, color, &clip);
+    }
+
+    stroke_corners(screen, &corners, radius, color, &clip);
+}
+
+pub fn draw_filled_rounded_rect(
+    screen: &mut Screen,
+    x: isize,
+    y: isize,
+    width: usize,
+    height: usize,
+    radius: usize,
+    color: u32,
+) {
+    let clip = ClipRect::screen(screen);
+    draw_filled_rounded_rect_clipped(screen, x, y, width, height, radius, color, clip);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn draw_filled_rounded_rect_clipped(
+    screen: &mut Screen,
+    x: isize,
+    y: isize,
+    width: usize,
+    height: usize,
+    radius: usize,
+    color: u32,
+    clip: ClipRect,
+) {
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let clip = clip.intersect(&ClipRect::screen(screen));
+    let radius = clamp_corner_radius(width, height, radius);
+    let corners = Corners::rect(x, y, width, height, radius);
+
+    for row in corners.top..=corners.bottom {
+        fill_span_clipped(screen, x, x + width as isize - 1, row, color, &clip);
+    }
+
+    fill_corners(screen, &corners, radius, color, &clip);
+}
+
+pub fn draw_text_scaled(screen: &mut Screen, x: isize, y: isize, text: &str, color: u32, scale: usize) {
+    for (index, character) in text.chars().enumerate() {
+        let char_x = x + (index * FONT_WIDTH * scale) as isize;
+        draw_char_scaled(screen, char_x, y, character, color, scale);
+    }
+}
+
+// Draws only the set pixels of the glyph, each one as a `scale`x`scale` block
+pub fn draw_char_scaled(screen: &mut Screen, x: isize, y: isize, character: char, color: u32, scale: usize) {
+    let bitmap = get_char_bitmap(character);
+
+    for (row, row_data) in bitmap.iter().enumerate().take(FONT_HEIGHT) {
+        for col in 0..FONT_WIDTH {
+            if (row_data >> (7 - col)) & 1 == 1 {
+                let block_x = x + (col * scale) as isize;
+                let block_y = y + (row * scale) as isize;
+                draw_filled_rect(screen, block_x, block_y, scale, scale, color);
+            }
+        }
+    }
+}
+
+fn clamp_corner_radius(width: usize, height: usize, radius: usize) -> isize {
+    radius.min((width - 1) / 2).min((height - 1) / 2) as isize
+}
+
+// Centers of the four corner arcs. A circle is the degenerate case where
+// all four centers coincide.
+struct Corners {
+    left: isize,
+    right: isize,
+    top: isize,
+    bottom: isize,
+}
+
+impl Corners {
+    fn circle(center_x: isize, center_y: isize) -> Self {
+        Self { left: center_x, right: center_x, top: center_y, bottom: center_y }
+    }
+
+    fn rect(x: isize, y: isize, width: usize, height: usize, radius: isize) -> Self {
+        Self {
+            left: x + radius,
+            right: x + width as isize - 1 - radius,
+            top: y + radius,
+            bottom: y + height as isize - 1 - radius,
+        }
+    }
+}
+
+// Midpoint circle algorithm: walks one octant and mirrors every step into the
+// other seven, handing each mirrored pair of (dx, dy) offsets to `visit`
+fn walk_midpoint_circle(radius: isize, mut visit: impl FnMut(isize, isize)) {
+    let mut x = radius;
+    let mut y = 0;
+    let mut decision = 1 - radius;
+
+    while x >= y {
+        visit(x, y);
+        visit(y, x);
+
+        y += 1;
+        if decision < 0 {
+            decision += 2 * y + 1;
+        } else {
+            x -= 1;
+            decision += 2 * (y - x) + 1;
+        }
+    }
+}
+
+fn stroke_corners(screen: &mut Screen, corners: &Corners, radius: isize, color: u32, clip: &ClipRect) {
+    walk_midpoint_circle(radius, |dx, dy| {
+        plot_clipped(screen, corners.right + dx, corners.bottom + dy, color, clip);
+        plot_clipped(screen, corners.left - dx, corners.bottom + dy, color, clip);
+        plot_clipped(screen, corners.right + dx, corners.top - dy, color, clip);
+        plot_clipped(screen, corners.left - dx, corners.top - dy, color, clip);
+    });
+}
+
+fn fill_corners(screen: &mut Screen, corners: &Corners, radius: isize, color: u32, clip: &ClipRect) {
+    walk_midpoint_circle(radius, |dx, dy| {
+        fill_span_clipped(screen, corners.left - dx, corners.right + dx, corners.top - dy, color, clip);
+        fill_span_clipped(screen, corners.left - dx, corners.right + dx, corners.bottom + dy, color, clip);
+    });
+}
+
+fn fill_span_clipped(screen: &mut Screen, x_start: isize, x_end: isize, y: isize, color: u32, clip: &ClipRect) {
+    if y < clip.y || y >= clip.y + clip.height as isize {
+        return;
+    }
+
+    let x_start = x_start.max(clip.x);
+    let x_end = x_end.min(clip.x + clip.width as isize - 1);
+    if x_start <= x_end {
+        screen.fill_span(x_start as usize, y as usize, (x_end - x_start + 1) as usize, color);
+    }
+}
+
+fn plot_clipped(screen: &mut Screen, x: isize, y: isize, color: u32, clip: &ClipRect) {
+    if clip.contains(x, y) {
+        screen.write_pixel(x as usize, y as usize, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    const WHITE: u32 = 0xFFFFFFFF;
+
+    fn lit_pixels(screen: &Screen) -> Vec<(usize, usize)> {
+        let mut pixels = Vec::new();
+        for y in 0..screen.height {
+            for x in 0..screen.width {
+                if screen.read_pixel(x, y) == Some(WHITE) {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn horizontal_and_vertical_lines_cover_both_endpoints() {
+        let mut screen = Screen::in_memory(8, 8);
+        draw_line(&mut screen, 1, 2, 5, 2, WHITE);
+        assert_eq!(lit_pixels(&screen), [(1, 2), (2, 2), (3, 2), (4, 2), (5, 2)]);
+
+        let mut screen = Screen::in_memory(8, 8);
+        draw_line(&mut screen, 3, 6, 3, 4, WHITE);
+        assert_eq!(lit_pixels(&screen), [(3, 4), (3, 5), (3, 6)]);
+    }
+
+    #[test]
+    fn diagonal_line_is_the_same_in_both_directions() {
+        let mut forward = Screen::in_memory(8, 8);
+        draw_line(&mut forward, 0, 0, 4, 4, WHITE);
+        assert_eq!(lit_pixels(&forward), [(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+
+        let mut backward = Screen::in_memory(8, 8);
+        draw_line(&mut backward, 4, 4, 0, 0, WHITE);
+        assert_eq!(lit_pixels(&backward), lit_pixels(&forward));
+    }
+
+    #[test]
+    fn steep_line_has_one_pixel_per_row() {
+        let mut screen = Screen::in_memory(8, 8);
+        draw_line(&mut screen, 0, 0, 2, 6, WHITE);
+
+        let pixels = lit_pixels(&screen);
+        assert_eq!(pixels.len(), 7);
+        assert!(pixels.iter().enumerate().all(|(row, &(_, y))| y == row));
+        assert_eq!((pixels[0], pixels[6]), ((0, 0), (2, 6)));
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_clip_rect_and_screen() {
+        let mut screen = Screen::in_memory(8, 8);
+        draw_line_clipped(&mut screen, -4, 3, 20, 3, WHITE, ClipRect::new(2, 0, 3, 8));
+        assert_eq!(lit_pixels(&screen), [(2, 3), (3, 3), (4, 3)]);
+
+        let mut screen = Screen::in_memory(8, 8);
+        draw_line(&mut screen, -2, -2, 10, 10, WHITE);
+        assert_eq!(lit_pixels(&screen).len(), 8);
+    }
+
+    #[test]
+    fn midpoint_circle_is_symmetric_about_both_axes_and_diagonals() {
+        let mut screen = Screen::in_memory(21, 21);
+        draw_circle(&mut screen, 10, 10, 7, WHITE);
+
+        let pixels = lit_pixels(&screen);
+        assert!(!pixels.is_empty());
+        for &(x, y) in &pixels {
+            let (dx, dy) = (x as isize - 10, y as isize - 10);
+            for (mirrored_x, mirrored_y) in [(-dx, dy), (dx, -dy), (dy, dx)] {
+                let mirrored = ((10 + mirrored_x) as usize, (10 + mirrored_y) as usize);
+                assert!(pixels.contains(&mirrored), "{:?} has no mirror {:?}", (x, y), mirrored);
+            }
+        }
+        assert!(pixels.contains(&(17, 10)) && pixels.contains(&(10, 3)));
+        assert!(!pixels.contains(&(10, 10)));
+    }
+
+    #[test]
+    fn filled_circle_covers_center_and_edges_but_not_corners() {
+        let mut screen = Screen::in_memory(21, 21);
+        draw_filled_circle(&mut screen, 10, 10, 5, WHITE);
+
+        let pixels = lit_pixels(&screen);
+        for inside in [(10, 10), (5, 10), (15, 10), (10, 5), (10, 15)] {
+            assert!(pixels.contains(&inside), "{:?} should be filled", inside);
+        }
+        for outside in [(4, 10), (16, 10), (5, 5), (15, 15)] {
+            assert!(!pixels.contains(&outside), "{:?} should be empty", outside);
+        }
+    }
+
+    #[test]
+    fn corner_radius_is_clamped_to_half_the_shorter_side() {
+        assert_eq!(clamp_corner_radius(40, 20, 4), 4);
+        assert_eq!(clamp_corner_radius(40, 20, 30), 9);
+        assert_eq!(clamp_corner_radius(1, 1, 5), 0);
+    }
+
+    #[test]
+    fn rounded_rect_leaves_its_corner_pixels_unset() {
+        let mut filled = Screen::in_memory(20, 12);
+        draw_filled_rounded_rect(&mut filled, 0, 0, 20, 12, 4, WHITE);
+        let mut outlined = Screen::in_memory(20, 12);
+        draw_rounded_rect(&mut outlined, 0, 0, 20, 12, 4, WHITE);
+
+        for screen in [&filled, &outlined] {
+            let pixels = lit_pixels(screen);
+            for corner in [(0, 0), (19, 0), (0, 11), (19, 11)] {
+                assert!(!pixels.contains(&corner), "{:?} should be rounded off", corner);
+            }
+            for edge in [(10, 0), (10, 11), (0, 6), (19, 6)] {
+                assert!(pixels.contains(&edge), "{:?} should be drawn", edge);
+            }
+        }
+        assert!(lit_pixels(&filled).contains(&(10, 6)));
+        assert!(!lit_pixels(&outlined).contains(&(10, 6)));
+    }
+
+    #[test]
+    fn blending_respects_alpha() {
+        let mut screen = Screen::in_memory(3, 1);
+        screen.fill_span(0, 0, 3, 0xFF204060);
+
+        blend_pixel(&mut screen, 0, 0, 0x00FFFFFF);
+        blend_pixel(&mut screen, 1, 0, 0xFFFFFFFF);
+        blend_pixel(&mut screen, 2, 0, 0x80FFFFFF);
+
+        assert_eq!(screen.read_pixel(0, 0), Some(0xFF204060));
+        assert_eq!(screen.read_pixel(1, 0), Some(0xFFFFFFFF));
+        assert_eq!(screen.read_pixel(2, 0), Some(0xFF90A0B0));
+    }
+
+    #[test]
+    fn half_white_over_black_is_mid_grey() {
+        let mut screen = Screen::in_memory(4, 4);
+        screen.clear_screen(0xFF000000);
+        draw_filled_rect_alpha(&mut screen, 1, 1, 2, 2, 0x80FFFFFF);
+
+        assert_eq!(screen.read_pixel(1, 1), Some(0xFF808080));
+        assert_eq!(screen.read_pixel(2, 2), Some(0xFF808080));
+        assert_eq!(screen.read_pixel(0, 0), Some(0xFF000000));
+        assert_eq!(screen.read_pixel(3, 3), Some(0xFF000000));
+    }
+
+    #[test]
+    fn scale_2_text_takes_16_pixels_per_character() {
+        // Unknown characters draw a box that fills the whole 8x8 cell
+        let mut screen = Screen::in_memory(40, 20);
+        draw_text_scaled(&mut screen, 0, 0, "\u{1}\u{1}", WHITE, 2);
+
+        let pixels = lit_pixels(&screen);
+        let right_edge = pixels.iter().map(|&(x, _)| x).max();
+        let bottom_edge = pixels.iter().map(|&(_, y)| y).max();
+        assert_eq!((right_edge, bottom_edge), (Some(31), Some(15)));
+        assert!(pixels.contains(&(16, 0)) && pixels.contains(&(15, 15)));
+        assert!(!pixels.contains(&(2, 2)));
+    }
+}
@@ -1,5 +1,7 @@
 pub mod screen;
 pub mod color_utils;
 pub mod bitmap;
+pub mod graphics;
+pub mod font;
 
 pub use screen::{Screen, init_screen};
@@ -1,23 +1,76 @@
-use super::color_utils::color_to_bytes;
+use super::color_utils::{color_to_bytes, bytes_to_color};
 use super::bitmap::Bitmap;
 use bootloader_api::info::PixelFormat;
 use bootloader_api::BootInfo;
+use spin::Once;
+
+// Raw description of the boot framebuffer, kept so the panic handler can
+// draw even though the console owns the `Screen`
+struct FramebufferDescription {
+    address: usize,
+    length: usize,
+    width: usize,
+    height: usize,
+    bytes_per_pixel: usize,
+    stride: usize,
+    pixel_format: PixelFormat,
+}
+
+static FRAMEBUFFER_DESCRIPTION: Once<FramebufferDescription> = Once::new();
 
 pub fn init_screen(boot_info: &'static mut BootInfo) -> Screen {
     let framebuffer = boot_info.framebuffer.as_mut().expect("No framebuffer found");
     let info = framebuffer.info();
+    let buffer = framebuffer.buffer_mut();
+    crate::kprintln!(
+        "Framebuffer: {}x{}, {} bytes per pixel, {:?}",
+        info.width,
+        info.height,
+        info.bytes_per_pixel,
+        info.pixel_format,
+    );
+
+    FRAMEBUFFER_DESCRIPTION.call_once(|| FramebufferDescription {
+        address: buffer.as_mut_ptr() as usize,
+        length: buffer.len(),
+        width: info.width,
+        height: info.height,
+        bytes_per_pixel: info.bytes_per_pixel,
+        stride: info.stride,
+        pixel_format: info.pixel_format,
+    });
+
     let display = Screen::new(
         info.width,
         info.height,
         info.bytes_per_pixel,
         info.stride,
-        framebuffer.buffer_mut(),
+        buffer,
         info.pixel_format,
     );
     
     return display;
 }
 
+// Creates a second `Screen` over the boot framebuffer. It aliases the one
+// owned by the console, so it is only safe once nothing else will draw again,
+// i.e. from the panic handler.
+pub unsafe fn panic_screen() -> Option<Screen> {
+    let description = FRAMEBUFFER_DESCRIPTION.get()?;
+    let framebuffer = unsafe {
+        core::slice::from_raw_parts_mut(description.address as *mut u8, description.length)
+    };
+
+    Some(Screen::new(
+        description.width,
+        description.height,
+        description.bytes_per_pixel,
+        description.stride,
+        framebuffer,
+        description.pixel_format,
+    ))
+}
+
 pub struct Screen {
     pub width: usize,
     pub height: usize,
@@ -58,6 +111,16 @@ impl Screen {
         return false;
     }
 
+    pub fn read_pixel(&self, x: usize, y: usize) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let offset = (y * self.stride + x) * self.bytes_per_pixel;
+        let bytes = self.framebuffer.get(offset..offset + self.bytes_per_pixel)?;
+        bytes_to_color(bytes, self.pixel_format)
+    }
+
     fn write_to_framebuffer(&mut self, x: usize, y: usize, bytes: &[u8]) -> bool {
         let offset = (y * self.stride + x) * self.bytes_per_pixel;
         if offset + self.bytes_per_pixel <= self.framebuffer.len() {
@@ -68,11 +131,77 @@ impl Screen {
         }
     }
 
-    pub fn clear_screen(&mut self, color: u32) {        
-        for y in 0..self.height {
-            for x in 0..self.width {
-                self.write_pixel(x, y, color);
+    // Fills `length` pixels of row `y` starting at `x`, clipped to the screen.
+    // The color is converted once and copied straight into the framebuffer.
+    pub fn fill_span(&mut self, x: usize, y: usize, length: usize, color: u32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        let Some(bytes) = color_to_bytes(color, self.pixel_format) else {
+            return;
+        };
+        if bytes[3] == 0 {
+            return;
+        }
+
+        let length = length.min(self.width - x);
+        let start = (y * self.stride + x) * self.bytes_per_pixel;
+        let end = (start + length * self.bytes_per_pixel).min(self.framebuffer.len());
+        let Some(span) = self.framebuffer.get_mut(start..end) else {
+            return;
+        };
+
+        for pixel in span.chunks_exact_mut(self.bytes_per_pixel) {
+            pixel.copy_from_slice(&bytes[..self.bytes_per_pixel]);
+        }
+    }
+
+    // Copies a rectangle of pixels to another position, clipped to the screen.
+    // Rows are visited in the direction that keeps overlapping regions intact,
+    // and `copy_within` handles overlap inside a row.
+    pub fn copy_rect(
+        &mut self,
+        src_x: usize,
+        src_y: usize,
+        width: usize,
+        height: usize,
+        dst_x: usize,
+        dst_y: usize,
+    ) {
+        let width = width
+            .min(self.width.saturating_sub(src_x))
+            .min(self.width.saturating_sub(dst_x));
+        let height = height
+            .min(self.height.saturating_sub(src_y))
+            .min(self.height.saturating_sub(dst_y));
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let row_bytes = width * self.bytes_per_pixel;
+        let copy_row = |screen: &mut Self, row: usize| {
+            let source = ((src_y + row) * screen.stride + src_x) * screen.bytes_per_pixel;
+            let destination = ((dst_y + row) * screen.stride + dst_x) * screen.bytes_per_pixel;
+            if source.max(destination) + row_bytes <= screen.framebuffer.len() {
+                screen.framebuffer.copy_within(source..source + row_bytes, destination);
+            }
+        };
+
+        if dst_y > src_y {
+            for row in (0..height).rev() {
+                copy_row(self, row);
             }
+        } else {
+            for row in 0..height {
+                copy_row(self, row);
+            }
+        }
+    }
+
+    pub fn clear_screen(&mut self, color: u32) {
+        for y in 0..self.height {
+            self.fill_span(0, y, self.width, color);
         }
     }
 
@@ -91,3 +220,12 @@ impl Screen {
         }
     }
 }
+
+#[cfg(test)]
+impl Screen {
+    // A 32-bit BGR screen over leaked host memory, starting out black
+    pub fn in_memory(width: usize, height: usize) -> Self {
+        let framebuffer = alloc::vec![0; width * height * 4].leak();
+        Self::new(width, height, 4, width, framebuffer, PixelFormat::Bgr)
+    }
+}
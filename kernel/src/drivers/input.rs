@@ -0,0 +1,152 @@
+// Input from every source goes through one FIFO queue, filled by the interrupt
+// handlers and drained by the main loop
+
+use spin::Mutex;
+use x86_64::instructions::interrupts::without_interrupts;
+use super::keyboard::KeyEvent;
+
+const EVENT_QUEUE_SIZE: usize = 256;
+
+static EVENT_QUEUE: Mutex<EventQueue> = Mutex::new(EventQueue::new());
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputEvent {
+    Key(KeyEvent),
+    Tick,
+}
+
+struct EventQueue {
+    events: [InputEvent; EVENT_QUEUE_SIZE],
+    write_position: usize,
+    read_position: usize,
+}
+
+impl EventQueue {
+    const fn new() -> Self {
+        Self {
+            events: [InputEvent::Tick; EVENT_QUEUE_SIZE],
+            write_position: 0,
+            read_position: 0,
+        }
+    }
+
+    // Drops the event when the queue is full
+    fn push(&mut self, event: InputEvent) {
+        // A consumer that fell behind only needs to know that time passed
+        if event == InputEvent::Tick && self.last() == Some(InputEvent::Tick) {
+            return;
+        }
+
+        let next_position = (self.write_position + 1) % self.events.len();
+        if next_position != self.read_position {
+            self.events[self.write_position] = event;
+            self.write_position = next_position;
+        }
+    }
+
+    fn last(&self) -> Option<InputEvent> {
+        if self.is_empty() {
+            None
+        } else {
+            let last_position = (self.write_position + self.events.len() - 1) % self.events.len();
+            Some(self.events[last_position])
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.read_position == self.write_position
+    }
+
+    fn pop(&mut self) -> Option<InputEvent> {
+        if self.is_empty() {
+            None
+        } else {
+            let event = self.events[self.read_position];
+            self.read_position = (self.read_position + 1) % self.events.len();
+            Some(event)
+        }
+    }
+}
+
+// Interrupt handlers push too, so callers must have interrupts disabled
+pub fn push(event: InputEvent) {
+    EVENT_QUEUE.lock().push(event);
+}
+
+pub fn try_pop() -> Option<InputEvent> {
+    without_interrupts(|| EVENT_QUEUE.lock().pop())
+}
+
+pub fn is_empty() -> bool {
+    without_interrupts(|| EVENT_QUEUE.lock().is_empty())
+}
+
+// Tests cannot use `try_pop`, since disabling interrupts faults on the host
+#[cfg(test)]
+pub fn drain() -> alloc::vec::Vec<InputEvent> {
+    let mut queue = EVENT_QUEUE.lock();
+    core::iter::from_fn(|| queue.pop()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::keyboard::Key;
+
+    fn key(c: char) -> InputEvent {
+        InputEvent::Key(KeyEvent { key: Key::Character(c), shift: false, ctrl: false, alt: false })
+    }
+
+    fn pop_all(queue: &mut EventQueue) -> alloc::vec::Vec<InputEvent> {
+        core::iter::from_fn(|| queue.pop()).collect()
+    }
+
+    #[test]
+    fn consecutive_ticks_are_coalesced() {
+        let mut queue = EventQueue::new();
+        queue.push(InputEvent::Tick);
+        queue.push(InputEvent::Tick);
+        queue.push(key('a'));
+        queue.push(InputEvent::Tick);
+        queue.push(InputEvent::Tick);
+
+        assert_eq!(pop_all(&mut queue), [InputEvent::Tick, key('a'), InputEvent::Tick]);
+    }
+
+    #[test]
+    fn a_tick_after_draining_is_queued_again() {
+        let mut queue = EventQueue::new();
+        queue.push(InputEvent::Tick);
+        assert_eq!(queue.pop(), Some(InputEvent::Tick));
+
+        queue.push(InputEvent::Tick);
+        assert_eq!(queue.pop(), Some(InputEvent::Tick));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn events_past_capacity_are_dropped() {
+        let mut queue = EventQueue::new();
+        // One slot stays free to tell a full queue from an empty one
+        for _ in 0..EVENT_QUEUE_SIZE - 1 {
+            queue.push(key('a'));
+        }
+        queue.push(key('b'));
+
+        let events = pop_all(&mut queue);
+        assert_eq!(events.len(), EVENT_QUEUE_SIZE - 1);
+        assert!(!events.contains(&key('b')));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn positions_wrap_around_the_buffer() {
+        let mut queue = EventQueue::new();
+        for round in 0..3 * EVENT_QUEUE_SIZE {
+            let c = char::from(b'a' + (round % 26) as u8);
+            queue.push(key(c));
+            assert_eq!(queue.pop(), Some(key(c)));
+        }
+        assert_eq!(queue.last(), None);
+    }
+}
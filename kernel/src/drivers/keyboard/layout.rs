@@ -0,0 +1,272 @@
+// Scancode tables for the supported physical keyboard layouts (Set 1 scancodes).
+// Characters outside of ASCII are stored as their Latin-1 byte value.
+
+use super::Modifiers;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardLayout {
+    Swiss,
+    UsQwerty,
+    Uk,
+    German,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DeadKey {
+    Circumflex,
+    Grave,
+    Acute,
+    Diaeresis,
+}
+
+#[derive(Clone, Copy)]
+pub enum KeyMapping {
+    Character(u8),
+    Dead(DeadKey),
+}
+
+const fn ch(character: u8) -> KeyMapping {
+    KeyMapping::Character(character)
+}
+
+const fn dead(dead_key: DeadKey) -> KeyMapping {
+    KeyMapping::Dead(dead_key)
+}
+
+impl KeyboardLayout {
+    pub const ALL: [KeyboardLayout; 4] = [
+        KeyboardLayout::Swiss,
+        KeyboardLayout::UsQwerty,
+        KeyboardLayout::Uk,
+        KeyboardLayout::German,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyboardLayout::Swiss => "ch",
+            KeyboardLayout::UsQwerty => "us",
+            KeyboardLayout::Uk => "uk",
+            KeyboardLayout::German => "de",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|layout| layout.name() == name)
+    }
+
+    pub fn map(self, scancode: u8, modifiers: Modifiers) -> Option<KeyMapping> {
+        // Caps Lock only affects letters, and Shift inverts it
+        if let Some(letter) = self.letter(scancode) {
+            let uppercase = modifiers.shift != modifiers.caps_lock;
+            let letter = if uppercase { letter.to_ascii_uppercase() } else { letter };
+            return Some(ch(letter));
+        }
+
+        match scancode {
+            0x39 => return Some(ch(b' ')),  // Space
+            0x1C => return Some(ch(b'\n')), // Enter
+            0x0E => return Some(ch(0x08)),  // Backspace (ASCII backspace)
+            0x0F => return Some(ch(b'\t')), // Tab
+            _ => {}
+        }
+
+        let (normal, shifted) = match self {
+            KeyboardLayout::Swiss => swiss_symbol(scancode)?,
+            KeyboardLayout::UsQwerty => us_symbol(scancode)?,
+            KeyboardLayout::Uk => uk_symbol(scancode)?,
+            KeyboardLayout::German => german_symbol(scancode)?,
+        };
+
+        Some(if modifiers.shift { shifted } else { normal })
+    }
+
+    fn letter(self, scancode: u8) -> Option<u8> {
+        // QWERTZ layouts swap Z and Y
+        let qwertz = matches!(self, KeyboardLayout::Swiss | KeyboardLayout::German);
+
+        let letter = match scancode {
+            // Top letter row
+            0x10 => b'q', 0x11 => b'w', 0x12 => b'e', 0x13 => b'r', 0x14 => b't',
+            0x15 => if qwertz { b'z' } else { b'y' },
+            0x16 => b'u', 0x17 => b'i', 0x18 => b'o', 0x19 => b'p',
+
+            // Middle letter row
+            0x1E => b'a', 0x1F => b's', 0x20 => b'd', 0x21 => b'f', 0x22 => b'g',
+            0x23 => b'h', 0x24 => b'j', 0x25 => b'k', 0x26 => b'l',
+
+            // Bottom letter row
+            0x2C => if qwertz { b'y' } else { b'z' },
+            0x2D => b'x', 0x2E => b'c', 0x2F => b'v', 0x30 => b'b', 0x31 => b'n', 0x32 => b'm',
+
+            _ => return None,
+        };
+
+        Some(letter)
+    }
+}
+
+fn swiss_symbol(scancode: u8) -> Option<(KeyMapping, KeyMapping)> {
+    let symbols = match scancode {
+        0x02 => (ch(b'1'), ch(b'+')), 0x03 => (ch(b'2'), ch(b'"')), 0x04 => (ch(b'3'), ch(b'*')),
+        0x05 => (ch(b'4'), ch(0xE7)), 0x06 => (ch(b'5'), ch(b'%')), 0x07 => (ch(b'6'), ch(b'&')),
+        0x08 => (ch(b'7'), ch(b'/')), 0x09 => (ch(b'8'), ch(b'(')), 0x0A => (ch(b'9'), ch(b')')),
+        0x0B => (ch(b'0'), ch(b'=')),
+        0x0C => (ch(b'\''), ch(b'?')),
+        0x0D => (dead(DeadKey::Circumflex), dead(DeadKey::Grave)),
+        0x1A => (ch(0xFC), ch(0xE8)), // ü è
+        0x1B => (dead(DeadKey::Diaeresis), ch(b'!')),
+        0x27 => (ch(0xF6), ch(0xE9)), // ö é
+        0x28 => (ch(0xE4), ch(0xE0)), // ä à
+        0x29 => (ch(0xA7), ch(0xB0)), // § °
+        0x2B => (ch(b'$'), ch(0xA3)), // $ £
+        0x33 => (ch(b','), ch(b';')), 0x34 => (ch(b'.'), ch(b':')), 0x35 => (ch(b'-'), ch(b'_')),
+        0x56 => (ch(b'<'), ch(b'>')),
+        _ => return None,
+    };
+
+    Some(symbols)
+}
+
+fn german_symbol(scancode: u8) -> Option<(KeyMapping, KeyMapping)> {
+    let symbols = match scancode {
+        0x02 => (ch(b'1'), ch(b'!')), 0x03 => (ch(b'2'), ch(b'"')), 0x04 => (ch(b'3'), ch(0xA7)),
+        0x05 => (ch(b'4'), ch(b'$')), 0x06 => (ch(b'5'), ch(b'%')), 0x07 => (ch(b'6'), ch(b'&')),
+        0x08 => (ch(b'7'), ch(b'/')), 0x09 => (ch(b'8'), ch(b'(')), 0x0A => (ch(b'9'), ch(b')')),
+        0x0B => (ch(b'0'), ch(b'=')),
+        0x0C => (ch(0xDF), ch(b'?')), // ß
+        0x0D => (dead(DeadKey::Acute), dead(DeadKey::Grave)),
+        0x1A => (ch(0xFC), ch(0xDC)), // ü Ü
+        0x1B => (ch(b'+'), ch(b'*')),
+        0x27 => (ch(0xF6), ch(0xD6)), // ö Ö
+        0x28 => (ch(0xE4), ch(0xC4)), // ä Ä
+        0x29 => (dead(DeadKey::Circumflex), ch(0xB0)),
+        0x2B => (ch(b'#'), ch(b'\'')),
+        0x33 => (ch(b','), ch(b';')), 0x34 => (ch(b'.'), ch(b':')), 0x35 => (ch(b'-'), ch(b'_')),
+        0x56 => (ch(b'<'), ch(b'>')),
+        _ => return None,
+    };
+
+    Some(symbols)
+}
+
+fn us_symbol(scancode: u8) -> Option<(KeyMapping, KeyMapping)> {
+    let symbols = match scancode {
+        0x02 => (ch(b'1'), ch(b'!')), 0x03 => (ch(b'2'), ch(b'@')), 0x04 => (ch(b'3'), ch(b'#')),
+        0x05 => (ch(b'4'), ch(b'$')), 0x06 => (ch(b'5'), ch(b'%')), 0x07 => (ch(b'6'), ch(b'^')),
+        0x08 => (ch(b'7'), ch(b'&')), 0x09 => (ch(b'8'), ch(b'*')), 0x0A => (ch(b'9'), ch(b'(')),
+        0x0B => (ch(b'0'), ch(b')')),
+        0x0C => (ch(b'-'), ch(b'_')), 0x0D => (ch(b'='), ch(b'+')),
+        0x1A => (ch(b'['), ch(b'{')), 0x1B => (ch(b']'), ch(b'}')),
+        0x27 => (ch(b';'), ch(b':')), 0x28 => (ch(b'\''), ch(b'"')),
+        0x29 => (ch(b'`'), ch(b'~')), 0x2B => (ch(b'\\'), ch(b'|')),
+        0x33 => (ch(b','), ch(b'<')), 0x34 => (ch(b'.'), ch(b'>')), 0x35 => (ch(b'/'), ch(b'?')),
+        _ => return None,
+    };
+
+    Some(symbols)
+}
+
+fn uk_symbol(scancode: u8) -> Option<(KeyMapping, KeyMapping)> {
+    // Same as US apart from a handful of keys
+    let symbols = match scancode {
+        0x03 => (ch(b'2'), ch(b'"')),
+        0x04 => (ch(b'3'), ch(0xA3)), // £
+        0x28 => (ch(b'\''), ch(b'@')),
+        0x29 => (ch(b'`'), ch(0xAC)), // ¬
+        0x2B => (ch(b'#'), ch(b'~')),
+        0x56 => (ch(b'\\'), ch(b'|')),
+        _ => return us_symbol(scancode),
+    };
+
+    Some(symbols)
+}
+
+impl DeadKey {
+    pub fn spacing_form(self) -> u8 {
+        match self {
+            DeadKey::Circumflex => b'^',
+            DeadKey::Grave => b'`',
+            DeadKey::Acute => 0xB4,
+            DeadKey::Diaeresis => 0xA8,
+        }
+    }
+
+    pub fn compose(self, character: u8) -> Option<u8> {
+        let composed = match (self, character) {
+            (DeadKey::Circumflex, b'a') => 0xE2, (DeadKey::Circumflex, b'e') => 0xEA,
+            (DeadKey::Circumflex, b'i') => 0xEE, (DeadKey::Circumflex, b'o') => 0xF4,
+            (DeadKey::Circumflex, b'u') => 0xFB, (DeadKey::Circumflex, b'A') => 0xC2,
+            (DeadKey::Circumflex, b'E') => 0xCA, (DeadKey::Circumflex, b'I') => 0xCE,
+            (DeadKey::Circumflex, b'O') => 0xD4, (DeadKey::Circumflex, b'U') => 0xDB,
+
+            (DeadKey::Grave, b'a') => 0xE0, (DeadKey::Grave, b'e') => 0xE8,
+            (DeadKey::Grave, b'i') => 0xEC, (DeadKey::Grave, b'o') => 0xF2,
+            (DeadKey::Grave, b'u') => 0xF9, (DeadKey::Grave, b'A') => 0xC0,
+            (DeadKey::Grave, b'E') => 0xC8, (DeadKey::Grave, b'I') => 0xCC,
+            (DeadKey::Grave, b'O') => 0xD2, (DeadKey::Grave, b'U') => 0xD9,
+
+            (DeadKey::Acute, b'a') => 0xE1, (DeadKey::Acute, b'e') => 0xE9,
+            (DeadKey::Acute, b'i') => 0xED, (DeadKey::Acute, b'o') => 0xF3,
+            (DeadKey::Acute, b'u') => 0xFA, (DeadKey::Acute, b'y') => 0xFD,
+            (DeadKey::Acute, b'A') => 0xC1, (DeadKey::Acute, b'E') => 0xC9,
+            (DeadKey::Acute, b'I') => 0xCD, (DeadKey::Acute, b'O') => 0xD3,
+            (DeadKey::Acute, b'U') => 0xDA, (DeadKey::Acute, b'Y') => 0xDD,
+
+            (DeadKey::Diaeresis, b'a') => 0xE4, (DeadKey::Diaeresis, b'e') => 0xEB,
+            (DeadKey::Diaeresis, b'i') => 0xEF, (DeadKey::Diaeresis, b'o') => 0xF6,
+            (DeadKey::Diaeresis, b'u') => 0xFC, (DeadKey::Diaeresis, b'y') => 0xFF,
+            (DeadKey::Diaeresis, b'A') => 0xC4, (DeadKey::Diaeresis, b'E') => 0xCB,
+            (DeadKey::Diaeresis, b'I') => 0xCF, (DeadKey::Diaeresis, b'O') => 0xD6,
+            (DeadKey::Diaeresis, b'U') => 0xDC,
+
+            _ => return None,
+        };
+
+        Some(composed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT: Modifiers = Modifiers { shift: true, ..Modifiers::new() };
+    const SHIFT_2: u8 = 0x03;
+
+    fn character(layout: KeyboardLayout, scancode: u8, modifiers: Modifiers) -> Option<u8> {
+        match layout.map(scancode, modifiers)? {
+            KeyMapping::Character(character) => Some(character),
+            KeyMapping::Dead(_) => None,
+        }
+    }
+
+    #[test]
+    fn shift_2_depends_on_layout() {
+        assert_eq!(character(KeyboardLayout::UsQwerty, SHIFT_2, SHIFT), Some(b'@'));
+        assert_eq!(character(KeyboardLayout::Uk, SHIFT_2, SHIFT), Some(b'"'));
+    }
+
+    #[test]
+    fn unshifted_2_is_the_digit() {
+        assert_eq!(character(KeyboardLayout::UsQwerty, SHIFT_2, Modifiers::new()), Some(b'2'));
+        assert_eq!(character(KeyboardLayout::Uk, SHIFT_2, Modifiers::new()), Some(b'2'));
+    }
+
+    #[test]
+    fn shift_turns_digits_into_symbols() {
+        assert_eq!(character(KeyboardLayout::UsQwerty, 0x02, SHIFT), Some(b'!'));
+        assert_eq!(character(KeyboardLayout::UsQwerty, 0x0B, SHIFT), Some(b')'));
+    }
+
+    #[test]
+    fn caps_lock_capitalizes_letters_only_and_shift_inverts_it() {
+        const A: u8 = 0x1E;
+        let caps_lock = Modifiers { caps_lock: true, ..Modifiers::new() };
+        let caps_lock_and_shift = Modifiers { shift: true, ..caps_lock };
+
+        assert_eq!(character(KeyboardLayout::UsQwerty, A, caps_lock), Some(b'A'));
+        assert_eq!(character(KeyboardLayout::UsQwerty, A, caps_lock_and_shift), Some(b'a'));
+        assert_eq!(character(KeyboardLayout::UsQwerty, SHIFT_2, caps_lock), Some(b'2'));
+        assert_eq!(character(KeyboardLayout::UsQwerty, SHIFT_2, caps_lock_and_shift), Some(b'@'));
+    }
+}
@@ -0,0 +1,499 @@
+mod layout;
+
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use spin::Mutex;
+use x86_64::instructions::interrupts::without_interrupts;
+use layout::{DeadKey, KeyMapping};
+use super::input::{self, InputEvent};
+use super::timer::{self, TICKS_PER_SECOND};
+
+pub use layout::KeyboardLayout;
+
+const KEYBOARD_DATA_PORT: u16 = 0x60;
+const EXTENDED_PREFIX: u8 = 0xE0;
+
+const LEFT_SHIFT_PRESSED: u8 = 0x2A;
+const RIGHT_SHIFT_PRESSED: u8 = 0x36;
+const LEFT_SHIFT_RELEASED: u8 = 0xAA;
+const RIGHT_SHIFT_RELEASED: u8 = 0xB6;
+
+// Right Ctrl and Alt send the same codes behind an 0xE0 prefix
+const CTRL_PRESSED: u8 = 0x1D;
+const CTRL_RELEASED: u8 = 0x9D;
+const ALT_PRESSED: u8 = 0x38;
+const ALT_RELEASED: u8 = 0xB8;
+const CAPS_LOCK_PRESSED: u8 = 0x3A;
+const CAPS_LOCK_RELEASED: u8 = 0xBA;
+const NUM_LOCK_PRESSED: u8 = 0x45;
+const NUM_LOCK_RELEASED: u8 = 0xC5;
+
+// Software typematic repeat, driven by timer ticks. The keyboard's own repeated
+// make codes are ignored so the rate does not depend on the controller.
+const REPEAT_DELAY_TICKS: u64 = TICKS_PER_SECOND / 2;
+const REPEAT_INTERVAL_TICKS: u64 = 2;
+
+const SCANCODE_QUEUE_SIZE: usize = 128;
+
+static SCANCODE_QUEUE: ScancodeQueue = ScancodeQueue::new();
+static KEYBOARD_STATE: Mutex<KeyboardState> = Mutex::new(KeyboardState::new());
+
+// Raw scancodes from the IRQ1 handler, decoded later outside the interrupt.
+// There is a single producer (the handler) and a single consumer (the main
+// loop), so the positions alone synchronize it and the handler never locks.
+struct ScancodeQueue {
+    scancodes: [AtomicU8; SCANCODE_QUEUE_SIZE],
+    write_position: AtomicUsize,
+    read_position: AtomicUsize,
+}
+
+impl ScancodeQueue {
+    const fn new() -> Self {
+        Self {
+            scancodes: [const { AtomicU8::new(0) }; SCANCODE_QUEUE_SIZE],
+            write_position: AtomicUsize::new(0),
+            read_position: AtomicUsize::new(0),
+        }
+    }
+
+    // Drops the scancode when the queue is full
+    fn push(&self, scancode: u8) {
+        let write_position = self.write_position.load(Ordering::Relaxed);
+        let next_position = (write_position + 1) % SCANCODE_QUEUE_SIZE;
+        if next_position == self.read_position.load(Ordering::Acquire) {
+            return;
+        }
+
+        self.scancodes[write_position].store(scancode, Ordering::Relaxed);
+        self.write_position.store(next_position, Ordering::Release);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.read_position.load(Ordering::Relaxed) == self.write_position.load(Ordering::Acquire)
+    }
+
+    fn pop(&self) -> Option<u8> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let read_position = self.read_position.load(Ordering::Relaxed);
+        let scancode = self.scancodes[read_position].load(Ordering::Relaxed);
+        self.read_position.store((read_position + 1) % SCANCODE_QUEUE_SIZE, Ordering::Release);
+        Some(scancode)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Key {
+    Character(char),
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+}
+
+// A key press together with the modifiers held at the time
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl KeyEvent {
+    const fn new(key: Key, modifiers: Modifiers) -> Self {
+        Self {
+            key,
+            shift: modifiers.shift,
+            ctrl: modifiers.ctrl,
+            alt: modifiers.alt,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub caps_lock: bool,
+    pub num_lock: bool,
+}
+
+impl Modifiers {
+    pub const fn new() -> Self {
+        Self {
+            shift: false,
+            ctrl: false,
+            alt: false,
+            caps_lock: false,
+            num_lock: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct HeldKey {
+    scancode: u8,
+    extended: bool,
+    next_repeat_tick: u64,
+}
+
+struct KeyboardState {
+    layout: KeyboardLayout,
+    modifiers: Modifiers,
+    pending_dead_key: Option<DeadKey>,
+    held_key: Option<HeldKey>,
+    // Lock keys toggle only on the first make code, not on typematic repeats
+    caps_lock_held: bool,
+    num_lock_held: bool,
+    // The previous byte was the 0xE0 prefix of an extended scancode
+    extended_prefix: bool,
+}
+
+impl KeyboardState {
+    const fn new() -> Self {
+        Self {
+            layout: KeyboardLayout::Swiss,
+            modifiers: Modifiers::new(),
+            pending_dead_key: None,
+            held_key: None,
+            caps_lock_held: false,
+            num_lock_held: false,
+            extended_prefix: false,
+        }
+    }
+
+    fn handle_scancode(&mut self, scancode: u8, now_ticks: u64) {
+        if scancode == EXTENDED_PREFIX {
+            self.extended_prefix = true;
+            return;
+        }
+        let extended = core::mem::take(&mut self.extended_prefix);
+
+        match scancode {
+            // Some keyboards wrap extended keys in fake Shift presses
+            LEFT_SHIFT_PRESSED | RIGHT_SHIFT_PRESSED | LEFT_SHIFT_RELEASED | RIGHT_SHIFT_RELEASED if extended => {}
+            LEFT_SHIFT_PRESSED | RIGHT_SHIFT_PRESSED => self.modifiers.shift = true,
+            LEFT_SHIFT_RELEASED | RIGHT_SHIFT_RELEASED => self.modifiers.shift = false,
+            CTRL_PRESSED => self.modifiers.ctrl = true,
+            CTRL_RELEASED => self.modifiers.ctrl = false,
+            ALT_PRESSED => self.modifiers.alt = true,
+            ALT_RELEASED => self.modifiers.alt = false,
+            CAPS_LOCK_PRESSED => {
+                if !core::mem::replace(&mut self.caps_lock_held, true) {
+                    self.modifiers.caps_lock = !self.modifiers.caps_lock;
+                }
+            }
+            CAPS_LOCK_RELEASED => self.caps_lock_held = false,
+            NUM_LOCK_PRESSED => {
+                if !core::mem::replace(&mut self.num_lock_held, true) {
+                    self.modifiers.num_lock = !self.modifiers.num_lock;
+                }
+            }
+            NUM_LOCK_RELEASED => self.num_lock_held = false,
+            _ => {
+                // Key releases have the high bit set
+                if scancode & 0x80 != 0 {
+                    let released = scancode & 0x7F;
+                    if self.held_key.is_some_and(|held| held.scancode == released && held.extended == extended) {
+                        self.held_key = None;
+                    }
+                    return;
+                }
+
+                if self.held_key.is_some_and(|held| held.scancode == scancode && held.extended == extended) {
+                    return;
+                }
+
+                self.press_key(scancode, extended, now_ticks);
+            }
+        }
+    }
+
+    // The keyboard only repeats the most recently pressed key, so any press
+    // replaces the held key, even one that produces nothing
+    fn press_key(&mut self, scancode: u8, extended: bool, now_ticks: u64) {
+        let repeatable = if extended {
+            let Some(key) = extended_key(scancode) else {
+                self.held_key = None;
+                return;
+            };
+            input::push(InputEvent::Key(KeyEvent::new(key, self.modifiers)));
+            true
+        } else {
+            let Some(mapping) = self.layout.map(scancode, self.modifiers) else {
+                self.held_key = None;
+                return;
+            };
+            self.apply_mapping(mapping);
+
+            // Dead keys only make sense as a single press
+            matches!(mapping, KeyMapping::Character(_))
+        };
+
+        self.held_key = repeatable.then_some(HeldKey {
+            scancode,
+            extended,
+            next_repeat_tick: now_ticks + REPEAT_DELAY_TICKS,
+        });
+    }
+
+    fn repeat_held_key(&mut self, now_ticks: u64) {
+        let Some(held) = self.held_key else {
+            return;
+        };
+        if now_ticks < held.next_repeat_tick {
+            return;
+        }
+
+        self.press_key(held.scancode, held.extended, now_ticks);
+        if let Some(held) = self.held_key.as_mut() {
+            held.next_repeat_tick = now_ticks + REPEAT_INTERVAL_TICKS;
+        }
+    }
+
+    fn apply_mapping(&mut self, mapping: KeyMapping) {
+        let modifiers = self.modifiers;
+        match (mapping, self.pending_dead_key.take()) {
+            (KeyMapping::Character(character), None) => emit_character(character, modifiers),
+            (KeyMapping::Character(b' '), Some(dead_key)) => {
+                emit_character(dead_key.spacing_form(), modifiers);
+            }
+            (KeyMapping::Character(character), Some(dead_key)) => {
+                if let Some(composed) = dead_key.compose(character) {
+                    emit_character(composed, modifiers);
+                } else {
+                    emit_character(dead_key.spacing_form(), modifiers);
+                    emit_character(character, modifiers);
+                }
+            }
+            (KeyMapping::Dead(dead_key), previous) => {
+                if let Some(previous) = previous {
+                    emit_character(previous.spacing_form(), modifiers);
+                }
+                self.pending_dead_key = Some(dead_key);
+            }
+        }
+    }
+}
+
+// Characters from the layouts are Latin-1 bytes
+fn emit_character(character: u8, modifiers: Modifiers) {
+    input::push(InputEvent::Key(KeyEvent::new(Key::Character(character as char), modifiers)));
+}
+
+fn extended_key(scancode: u8) -> Option<Key> {
+    let key = match scancode {
+        0x48 => Key::ArrowUp,
+        0x50 => Key::ArrowDown,
+        0x4B => Key::ArrowLeft,
+        0x4D => Key::ArrowRight,
+        0x1C => Key::Character('\n'), // Keypad Enter
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+// Called from the timer interrupt to re-emit a held key
+pub fn repeat_held_key(now_ticks: u64) {
+    KEYBOARD_STATE.lock().repeat_held_key(now_ticks);
+}
+
+// Called from the IRQ1 handler
+pub fn handle_interrupt() {
+    SCANCODE_QUEUE.push(read_scancode());
+}
+
+// Decodes queued scancodes into key events
+pub fn process_scancodes() {
+    while let Some(scancode) = SCANCODE_QUEUE.pop() {
+        without_interrupts(|| KEYBOARD_STATE.lock().handle_scancode(scancode, timer::ticks()));
+    }
+}
+
+pub fn has_pending_scancodes() -> bool {
+    !SCANCODE_QUEUE.is_empty()
+}
+
+fn read_scancode() -> u8 {
+    unsafe {
+        let scancode: u8;
+        core::arch::asm!("in al, dx", in("dx") KEYBOARD_DATA_PORT, out("al") scancode);
+        scancode
+    }
+}
+
+pub fn modifiers() -> Modifiers {
+    without_interrupts(|| KEYBOARD_STATE.lock().modifiers)
+}
+
+pub fn set_layout(layout: KeyboardLayout) {
+    without_interrupts(|| KEYBOARD_STATE.lock().layout = layout);
+}
+
+pub fn layout() -> KeyboardLayout {
+    without_interrupts(|| KEYBOARD_STATE.lock().layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    const A_PRESSED: u8 = 0x1E;
+    const F1_PRESSED: u8 = 0x3B;
+    const ARROW_UP_PRESSED: u8 = 0x48;
+    const ARROW_RIGHT_PRESSED: u8 = 0x4D;
+
+    // Tests share the global input queue, so they take turns
+    static INPUT_QUEUE_USERS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn keys(events: Vec<InputEvent>) -> Vec<Key> {
+        events
+            .into_iter()
+            .filter_map(|event| match event {
+                InputEvent::Key(key_event) => Some(key_event.key),
+                InputEvent::Tick => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn held_key_repeats_after_the_delay_at_the_interval() {
+        let _queue = INPUT_QUEUE_USERS.lock().unwrap();
+        input::drain();
+        let mut state = KeyboardState::new();
+        state.layout = KeyboardLayout::UsQwerty;
+
+        state.press_key(A_PRESSED, false, 0);
+        assert_eq!(keys(input::drain()), [Key::Character('a')]);
+
+        let mut emitted_at = Vec::new();
+        for tick in 1..=REPEAT_DELAY_TICKS + 3 * REPEAT_INTERVAL_TICKS {
+            state.repeat_held_key(tick);
+            if !input::drain().is_empty() {
+                emitted_at.push(tick);
+            }
+        }
+
+        let expected: Vec<u64> = (0..=3).map(|repeat| REPEAT_DELAY_TICKS + repeat * REPEAT_INTERVAL_TICKS).collect();
+        assert_eq!(emitted_at, expected);
+    }
+
+    #[test]
+    fn extended_keys_repeat_too() {
+        let _queue = INPUT_QUEUE_USERS.lock().unwrap();
+        input::drain();
+        let mut state = KeyboardState::new();
+
+        state.press_key(ARROW_UP_PRESSED, true, 0);
+        state.repeat_held_key(REPEAT_DELAY_TICKS);
+        assert_eq!(keys(input::drain()), [Key::ArrowUp, Key::ArrowUp]);
+    }
+
+    #[test]
+    fn pressing_an_unmapped_key_stops_the_previous_repeat() {
+        let _queue = INPUT_QUEUE_USERS.lock().unwrap();
+        input::drain();
+        let mut state = KeyboardState::new();
+
+        state.press_key(A_PRESSED, false, 0);
+        state.press_key(F1_PRESSED, false, 1);
+        state.repeat_held_key(REPEAT_DELAY_TICKS + 1);
+
+        assert!(state.held_key.is_none());
+        assert_eq!(keys(input::drain()).len(), 1);
+    }
+
+    #[test]
+    fn shift_right_arrives_as_a_right_arrow_with_shift_set() {
+        let _queue = INPUT_QUEUE_USERS.lock().unwrap();
+        input::drain();
+        let mut state = KeyboardState::new();
+
+        for scancode in [LEFT_SHIFT_PRESSED, EXTENDED_PREFIX, ARROW_RIGHT_PRESSED] {
+            state.handle_scancode(scancode, 0);
+        }
+
+        let events = input::drain();
+        let [InputEvent::Key(event)] = events[..] else {
+            panic!("expected one key event, got {:?}", events);
+        };
+        assert_eq!(event.key, Key::ArrowRight);
+        assert!(event.shift && !event.ctrl && !event.alt);
+    }
+
+    #[test]
+    fn caps_lock_toggles_once_per_press_despite_typematic_repeats() {
+        let mut state = KeyboardState::new();
+
+        for _ in 0..5 {
+            state.handle_scancode(CAPS_LOCK_PRESSED, 0);
+        }
+        assert!(state.modifiers.caps_lock);
+
+        state.handle_scancode(CAPS_LOCK_RELEASED, 0);
+        assert!(state.modifiers.caps_lock);
+
+        state.handle_scancode(CAPS_LOCK_PRESSED, 0);
+        state.handle_scancode(CAPS_LOCK_PRESSED, 0);
+        state.handle_scancode(CAPS_LOCK_RELEASED, 0);
+        assert!(!state.modifiers.caps_lock);
+    }
+
+    #[test]
+    fn num_lock_toggles_independently_of_caps_lock() {
+        let mut state = KeyboardState::new();
+
+        state.handle_scancode(NUM_LOCK_PRESSED, 0);
+        state.handle_scancode(NUM_LOCK_PRESSED, 0);
+        state.handle_scancode(NUM_LOCK_RELEASED, 0);
+
+        assert!(state.modifiers.num_lock);
+        assert!(!state.modifiers.caps_lock);
+    }
+
+    #[test]
+    fn scancode_queue_starts_empty_and_keeps_order() {
+        let queue = ScancodeQueue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+
+        queue.push(A_PRESSED);
+        queue.push(F1_PRESSED);
+        assert!(!queue.is_empty());
+        assert_eq!(queue.pop(), Some(A_PRESSED));
+        assert_eq!(queue.pop(), Some(F1_PRESSED));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn full_scancode_queue_drops_new_scancodes() {
+        let queue = ScancodeQueue::new();
+        // One slot stays free to tell a full queue from an empty one
+        for _ in 0..SCANCODE_QUEUE_SIZE - 1 {
+            queue.push(A_PRESSED);
+        }
+        queue.push(F1_PRESSED);
+
+        let scancodes: Vec<u8> = core::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(scancodes.len(), SCANCODE_QUEUE_SIZE - 1);
+        assert!(scancodes.iter().all(|&scancode| scancode == A_PRESSED));
+    }
+
+    #[test]
+    fn scancode_queue_positions_wrap_around() {
+        let queue = ScancodeQueue::new();
+        for round in 0..3 * SCANCODE_QUEUE_SIZE {
+            let scancode = round as u8;
+            queue.push(scancode);
+            queue.push(scancode.wrapping_add(1));
+            assert_eq!(queue.pop(), Some(scancode));
+            assert_eq!(queue.pop(), Some(scancode.wrapping_add(1)));
+        }
+        assert!(queue.is_empty());
+    }
+}
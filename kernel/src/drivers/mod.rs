@@ -3,8 +3,14 @@ pub mod apic;
 pub mod timer;
 pub mod keyboard;
 pub mod display;
+pub mod rtc;
+pub mod speaker;
+pub mod serial;
+pub mod input;
 
-pub fn init() {
+pub fn init(physical_memory_offset: Option<u64>) {
+    serial::init();
+    apic::init(physical_memory_offset);
     pic::init();
     timer::init();
 }
@@ -28,8 +28,16 @@ unsafe fn write_to_port(port: u16, value: u8) {
     }
 }
 
+unsafe fn read_from_port(port: u16) -> u8 {
+    let value: u8;
+    unsafe {
+        core::arch::asm!("in al, dx", in("dx") port, out("al") value);
+    }
+    value
+}
+
 pub fn init() {
-    super::apic::disable();
+    super::apic::enter_pic_mode();
     
     unsafe {
         write_to_port(MASTER_PIC_COMMAND_PORT, INIT_COMMAND);
@@ -64,3 +72,17 @@ pub fn send_eoi(irq_number: u8) {
         write_to_port(MASTER_PIC_COMMAND_PORT, END_OF_INTERRUPT);
     }
 }
+
+pub fn set_irq_masked(irq_number: u8, masked: bool) {
+    let (port, bit) = if irq_number < 8 {
+        (MASTER_PIC_DATA_PORT, irq_number)
+    } else {
+        (SLAVE_PIC_DATA_PORT, irq_number - 8)
+    };
+
+    unsafe {
+        let mask = read_from_port(port);
+        let mask = if masked { mask | (1 << bit) } else { mask & !(1 << bit) };
+        write_to_port(port, mask);
+    }
+}
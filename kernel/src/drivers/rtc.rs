@@ -0,0 +1,157 @@
+const CMOS_ADDRESS_PORT: u16 = 0x70;
+const CMOS_DATA_PORT: u16 = 0x71;
+
+const REGISTER_SECONDS: u8 = 0x00;
+const REGISTER_MINUTES: u8 = 0x02;
+const REGISTER_HOURS: u8 = 0x04;
+const REGISTER_DAY: u8 = 0x07;
+const REGISTER_MONTH: u8 = 0x08;
+const REGISTER_YEAR: u8 = 0x09;
+const REGISTER_STATUS_A: u8 = 0x0A;
+const REGISTER_STATUS_B: u8 = 0x0B;
+
+const STATUS_A_UPDATE_IN_PROGRESS: u8 = 0x80;
+const STATUS_B_24_HOUR_MODE: u8 = 0x02;
+const STATUS_B_BINARY_MODE: u8 = 0x04;
+const HOURS_PM_BIT: u8 = 0x80;
+
+const CENTURY_BASE: u16 = 2000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub day: u8,
+    pub month: u8,
+    pub year: u16,
+}
+
+unsafe fn write_to_port(port: u16, value: u8) {
+    unsafe {
+        core::arch::asm!("out dx, al", in("dx") port, in("al") value);
+    }
+}
+
+unsafe fn read_from_port(port: u16) -> u8 {
+    let value: u8;
+    unsafe {
+        core::arch::asm!("in al, dx", in("dx") port, out("al") value);
+    }
+    value
+}
+
+fn read_register(register: u8) -> u8 {
+    unsafe {
+        write_to_port(CMOS_ADDRESS_PORT, register);
+        read_from_port(CMOS_DATA_PORT)
+    }
+}
+
+fn update_in_progress() -> bool {
+    read_register(REGISTER_STATUS_A) & STATUS_A_UPDATE_IN_PROGRESS != 0
+}
+
+pub fn bcd_to_binary(value: u8) -> u8 {
+    (value & 0x0F) + (value >> 4) * 10
+}
+
+fn read_raw() -> DateTime {
+    while update_in_progress() {
+        core::hint::spin_loop();
+    }
+
+    DateTime {
+        hour: read_register(REGISTER_HOURS),
+        minute: read_register(REGISTER_MINUTES),
+        second: read_register(REGISTER_SECONDS),
+        day: read_register(REGISTER_DAY),
+        month: read_register(REGISTER_MONTH),
+        year: read_register(REGISTER_YEAR) as u16,
+    }
+}
+
+pub fn now() -> DateTime {
+    // The RTC may tick over between register reads, so read until two
+    // consecutive samples agree
+    let mut time = read_raw();
+    loop {
+        let next = read_raw();
+        if next == time {
+            break;
+        }
+        time = next;
+    }
+
+    decode(time, read_register(REGISTER_STATUS_B))
+}
+
+// Converts raw register values to binary, 24 hour time according to the
+// format bits in status register B
+fn decode(mut time: DateTime, status_b: u8) -> DateTime {
+    let pm = time.hour & HOURS_PM_BIT != 0;
+    time.hour &= !HOURS_PM_BIT;
+
+    if status_b & STATUS_B_BINARY_MODE == 0 {
+        time.hour = bcd_to_binary(time.hour);
+        time.minute = bcd_to_binary(time.minute);
+        time.second = bcd_to_binary(time.second);
+        time.day = bcd_to_binary(time.day);
+        time.month = bcd_to_binary(time.month);
+        time.year = bcd_to_binary(time.year as u8) as u16;
+    }
+
+    if status_b & STATUS_B_24_HOUR_MODE == 0 {
+        // 12 hour mode: 12 AM is midnight, 12 PM is noon
+        time.hour %= 12;
+        if pm {
+            time.hour += 12;
+        }
+    }
+
+    time.year += CENTURY_BASE;
+    time
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BCD_24_HOUR: u8 = STATUS_B_24_HOUR_MODE;
+    const BCD_12_HOUR: u8 = 0;
+
+    fn raw(hour: u8, minute: u8) -> DateTime {
+        DateTime { hour, minute, second: 0x30, day: 0x31, month: 0x12, year: 0x26 }
+    }
+
+    #[test]
+    fn bcd_digits_become_binary() {
+        assert_eq!(bcd_to_binary(0x00), 0);
+        assert_eq!(bcd_to_binary(0x09), 9);
+        assert_eq!(bcd_to_binary(0x10), 10);
+        assert_eq!(bcd_to_binary(0x59), 59);
+    }
+
+    #[test]
+    fn decodes_bcd_date_and_time() {
+        let time = decode(raw(0x23, 0x45), BCD_24_HOUR);
+        assert_eq!((time.hour, time.minute, time.second), (23, 45, 30));
+        assert_eq!((time.day, time.month, time.year), (31, 12, 2026));
+    }
+
+    #[test]
+    fn twelve_hour_mode_maps_to_24_hours() {
+        let hour = |raw_hour| decode(raw(raw_hour, 0), BCD_12_HOUR).hour;
+        assert_eq!(hour(0x12), 0);
+        assert_eq!(hour(0x01), 1);
+        assert_eq!(hour(0x12 | HOURS_PM_BIT), 12);
+        assert_eq!(hour(0x11 | HOURS_PM_BIT), 23);
+    }
+
+    #[test]
+    fn binary_mode_values_are_left_alone() {
+        let time = DateTime { hour: 21, minute: 7, second: 0, day: 1, month: 2, year: 26 };
+        let decoded = decode(time, STATUS_B_BINARY_MODE | STATUS_B_24_HOUR_MODE);
+        assert_eq!((decoded.hour, decoded.minute, decoded.year), (21, 7, 2026));
+    }
+}
@@ -0,0 +1,163 @@
+use core::fmt::{self, Write};
+use spin::Mutex;
+use x86_64::instructions::interrupts::without_interrupts;
+
+const COM1_PORT: u16 = 0x3F8;
+
+// Register offsets from the base port
+const DATA_REGISTER: u16 = 0;
+const INTERRUPT_ENABLE_REGISTER: u16 = 1;
+const FIFO_CONTROL_REGISTER: u16 = 2;
+const LINE_CONTROL_REGISTER: u16 = 3;
+const MODEM_CONTROL_REGISTER: u16 = 4;
+const LINE_STATUS_REGISTER: u16 = 5;
+
+const UART_CLOCK_BAUD: u32 = 115200;
+const BAUD_RATE: u32 = 38400;
+
+const LINE_CONTROL_DIVISOR_LATCH: u8 = 0x80;
+const LINE_CONTROL_8N1: u8 = 0x03;
+const FIFO_ENABLE_AND_CLEAR_14_BYTES: u8 = 0xC7;
+const MODEM_CONTROL_LOOPBACK: u8 = 0x1E;
+const MODEM_CONTROL_NORMAL: u8 = 0x0F;
+const LINE_STATUS_DATA_READY: u8 = 0x01;
+const LINE_STATUS_TRANSMIT_EMPTY: u8 = 0x20;
+
+const LOOPBACK_TEST_BYTE: u8 = 0xAE;
+
+pub static SERIAL: Mutex<SerialPort> = Mutex::new(SerialPort::new(COM1_PORT));
+
+unsafe fn write_to_port(port: u16, value: u8) {
+    unsafe {
+        core::arch::asm!("out dx, al", in("dx") port, in("al") value);
+    }
+}
+
+unsafe fn read_from_port(port: u16) -> u8 {
+    let value: u8;
+    unsafe {
+        core::arch::asm!("in al, dx", in("dx") port, out("al") value);
+    }
+    value
+}
+
+pub fn baud_divisor(baud_rate: u32) -> u16 {
+    (UART_CLOCK_BAUD / baud_rate.max(1)).clamp(1, u16::MAX as u32) as u16
+}
+
+pub struct SerialPort {
+    base_port: u16,
+    available: bool,
+}
+
+impl SerialPort {
+    const fn new(base_port: u16) -> Self {
+        Self {
+            base_port,
+            available: false,
+        }
+    }
+
+    fn init(&mut self) {
+        let divisor = baud_divisor(BAUD_RATE);
+
+        unsafe {
+            write_to_port(self.base_port + INTERRUPT_ENABLE_REGISTER, 0x00);
+
+            write_to_port(self.base_port + LINE_CONTROL_REGISTER, LINE_CONTROL_DIVISOR_LATCH);
+            write_to_port(self.base_port + DATA_REGISTER, (divisor & 0xFF) as u8);
+            write_to_port(self.base_port + INTERRUPT_ENABLE_REGISTER, (divisor >> 8) as u8);
+            write_to_port(self.base_port + LINE_CONTROL_REGISTER, LINE_CONTROL_8N1);
+
+            write_to_port(self.base_port + FIFO_CONTROL_REGISTER, FIFO_ENABLE_AND_CLEAR_14_BYTES);
+
+            // Send a byte to ourselves to check the UART is actually present
+            write_to_port(self.base_port + MODEM_CONTROL_REGISTER, MODEM_CONTROL_LOOPBACK);
+            write_to_port(self.base_port + DATA_REGISTER, LOOPBACK_TEST_BYTE);
+            self.available = read_from_port(self.base_port + LINE_STATUS_REGISTER) & LINE_STATUS_DATA_READY != 0
+                && read_from_port(self.base_port + DATA_REGISTER) == LOOPBACK_TEST_BYTE;
+
+            write_to_port(self.base_port + MODEM_CONTROL_REGISTER, MODEM_CONTROL_NORMAL);
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) {
+        if !self.available {
+            return;
+        }
+
+        unsafe {
+            while read_from_port(self.base_port + LINE_STATUS_REGISTER) & LINE_STATUS_TRANSMIT_EMPTY == 0 {
+                core::hint::spin_loop();
+            }
+            write_to_port(self.base_port + DATA_REGISTER, byte);
+        }
+    }
+}
+
+impl Write for SerialPort {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for byte in text.bytes() {
+            if byte == b'\n' {
+                self.write_byte(b'\r');
+            }
+            self.write_byte(byte);
+        }
+        Ok(())
+    }
+}
+
+pub fn init() {
+    SERIAL.lock().init();
+}
+
+#[doc(hidden)]
+pub fn _print(arguments: fmt::Arguments) {
+    without_interrupts(|| {
+        let _ = SERIAL.lock().write_fmt(arguments);
+    });
+}
+
+// Used from the panic handler, where the lock may be held by the code that panicked
+pub fn print_panic(arguments: fmt::Arguments) {
+    unsafe {
+        SERIAL.force_unlock();
+    }
+    _print(arguments);
+}
+
+#[macro_export]
+macro_rules! kprint {
+    ($($arg:tt)*) => {
+        $crate::drivers::serial::_print(format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! kprintln {
+    () => {
+        $crate::kprint!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::kprint!("{}\n", format_args!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baud_divisor_divides_the_uart_clock() {
+        assert_eq!(baud_divisor(115200), 1);
+        assert_eq!(baud_divisor(BAUD_RATE), 3);
+        assert_eq!(baud_divisor(9600), 12);
+    }
+
+    #[test]
+    fn baud_divisor_is_clamped_to_the_latch_range() {
+        assert_eq!(baud_divisor(230400), 1);
+        assert_eq!(baud_divisor(1), u16::MAX);
+        assert_eq!(baud_divisor(0), u16::MAX);
+    }
+}
@@ -0,0 +1,81 @@
+use super::timer::{self, PIT_BASE_FREQUENCY_HZ};
+
+const PIT_CHANNEL_2_DATA_PORT: u16 = 0x42;
+const PIT_COMMAND_PORT: u16 = 0x43;
+const SPEAKER_CONTROL_PORT: u16 = 0x61;
+
+const CHANNEL_2_SQUARE_WAVE_BINARY: u8 = 0xB6;
+// Bit 0 gates PIT channel 2, bit 1 connects it to the speaker
+const SPEAKER_ENABLE_BITS: u8 = 0x03;
+
+unsafe fn write_to_port(port: u16, value: u8) {
+    unsafe {
+        core::arch::asm!("out dx, al", in("dx") port, in("al") value);
+    }
+}
+
+unsafe fn read_from_port(port: u16) -> u8 {
+    let value: u8;
+    unsafe {
+        core::arch::asm!("in al, dx", in("dx") port, out("al") value);
+    }
+    value
+}
+
+pub fn frequency_to_divisor(frequency_hz: u32) -> u16 {
+    let divisor = PIT_BASE_FREQUENCY_HZ / frequency_hz.max(1) as u64;
+    divisor.clamp(1, u16::MAX as u64) as u16
+}
+
+pub fn play_tone(frequency_hz: u32) {
+    let divisor = frequency_to_divisor(frequency_hz);
+
+    unsafe {
+        write_to_port(PIT_COMMAND_PORT, CHANNEL_2_SQUARE_WAVE_BINARY);
+        write_to_port(PIT_CHANNEL_2_DATA_PORT, (divisor & 0xFF) as u8);
+        write_to_port(PIT_CHANNEL_2_DATA_PORT, (divisor >> 8) as u8);
+
+        let control = read_from_port(SPEAKER_CONTROL_PORT);
+        if control & SPEAKER_ENABLE_BITS != SPEAKER_ENABLE_BITS {
+            write_to_port(SPEAKER_CONTROL_PORT, control | SPEAKER_ENABLE_BITS);
+        }
+    }
+}
+
+pub fn stop() {
+    unsafe {
+        let control = read_from_port(SPEAKER_CONTROL_PORT);
+        write_to_port(SPEAKER_CONTROL_PORT, control & !SPEAKER_ENABLE_BITS);
+    }
+}
+
+// Blocks for the duration of the tone, rounded up to whole timer ticks
+pub fn beep(frequency_hz: u32, duration_ms: u64) {
+    play_tone(frequency_hz);
+    timer::sleep_ms(duration_ms.max(1));
+    stop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_hz_does_not_divide_by_zero() {
+        assert_eq!(frequency_to_divisor(0), frequency_to_divisor(1));
+        assert_eq!(frequency_to_divisor(0), u16::MAX);
+    }
+
+    #[test]
+    fn audible_frequencies_divide_the_pit_clock() {
+        assert_eq!(frequency_to_divisor(440), (PIT_BASE_FREQUENCY_HZ / 440) as u16);
+        assert_eq!(frequency_to_divisor(1000), 1193);
+    }
+
+    #[test]
+    fn frequencies_above_the_pit_clock_clamp_to_one() {
+        assert_eq!(frequency_to_divisor(PIT_BASE_FREQUENCY_HZ as u32), 1);
+        assert_eq!(frequency_to_divisor(2_000_000), 1);
+        assert_eq!(frequency_to_divisor(u32::MAX), 1);
+    }
+}
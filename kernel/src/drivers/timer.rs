@@ -1,12 +1,23 @@
-use core::sync::atomic::{AtomicU64, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use x86_64::instructions::interrupts::without_interrupts;
+use super::{apic, pic};
+use super::input::{self, InputEvent};
+use crate::cpu::idt::TIMER_INTERRUPT_VECTOR;
 
 const TIMER_DATA_PORT: u16 = 0x40;
 const TIMER_COMMAND_PORT: u16 = 0x43;
 
 const CHANNEL_0_RATE_GENERATOR_BINARY: u8 = 0x34;
 const MAX_FREQUENCY_DIVIDER: u16 = 65535;
+pub const PIT_BASE_FREQUENCY_HZ: u64 = 1_193_182;
+
+pub const TICKS_PER_SECOND: u64 = PIT_BASE_FREQUENCY_HZ / MAX_FREQUENCY_DIVIDER as u64;
+
+const TIMER_IRQ: u8 = 0;
+const APIC_CALIBRATION_TICKS: u64 = 4;
 
 static TIMER_TICKS: AtomicU64 = AtomicU64::new(0);
+static USING_APIC_TIMER: AtomicBool = AtomicBool::new(false);
 
 unsafe fn write_to_port(port: u16, value: u8) {
     unsafe {
@@ -29,8 +40,143 @@ pub fn init() {
 
 pub fn tick() {
     TIMER_TICKS.fetch_add(1, Ordering::Relaxed);
+    input::push(InputEvent::Tick);
 }
 
 pub fn ticks() -> u64 {
     TIMER_TICKS.load(Ordering::SeqCst)
 }
+
+// Uses the exact PIT period rather than the rounded TICKS_PER_SECOND so the
+// clock does not drift by ~1% against wall time
+pub fn ticks_to_ms(ticks: u64) -> u64 {
+    ticks * MAX_FREQUENCY_DIVIDER as u64 * 1000 / PIT_BASE_FREQUENCY_HZ
+}
+
+pub fn ms_to_ticks(ms: u64) -> u64 {
+    (ms * PIT_BASE_FREQUENCY_HZ).div_ceil(MAX_FREQUENCY_DIVIDER as u64 * 1000)
+}
+
+pub fn uptime_ms() -> u64 {
+    ticks_to_ms(ticks())
+}
+
+// Waits at least `ms` milliseconds, rounded up to whole timer ticks
+pub fn sleep_ms(ms: u64) {
+    let duration_ticks = ms_to_ticks(ms);
+    let start = ticks();
+
+    while ticks().wrapping_sub(start) < duration_ticks {
+        core::hint::spin_loop();
+    }
+}
+
+pub fn send_eoi() {
+    if USING_APIC_TIMER.load(Ordering::Relaxed) {
+        apic::send_eoi();
+    } else {
+        pic::send_eoi(TIMER_IRQ);
+    }
+}
+
+pub fn tick_source_name() -> &'static str {
+    if USING_APIC_TIMER.load(Ordering::Relaxed) { "apic" } else { "pit" }
+}
+
+// Initial count that makes the APIC fire at the same rate as the PIT, given
+// how many APIC ticks were counted over a number of PIT ticks
+pub fn apic_initial_count(elapsed_apic_ticks: u32, elapsed_pit_ticks: u64) -> u32 {
+    (elapsed_apic_ticks as u64 / elapsed_pit_ticks.max(1)).clamp(1, u32::MAX as u64) as u32
+}
+
+// Switches the tick source to the local APIC timer, calibrated against the
+// PIT so `ticks()` keeps counting at TICKS_PER_SECOND. Stays on the PIT if
+// the APIC is unavailable.
+pub fn use_apic_timer() -> Result<(), &'static str> {
+    if USING_APIC_TIMER.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+
+    apic::enable()?;
+
+    // Start measuring on a tick edge
+    let edge = ticks();
+    while ticks() == edge {
+        core::hint::spin_loop();
+    }
+
+    apic::start_calibration_countdown();
+    let start = ticks();
+    while ticks() - start < APIC_CALIBRATION_TICKS {
+        core::hint::spin_loop();
+    }
+    let elapsed_apic_ticks = apic::stop_calibration_countdown();
+    let initial_count = apic_initial_count(elapsed_apic_ticks, APIC_CALIBRATION_TICKS);
+
+    without_interrupts(|| {
+        pic::set_irq_masked(TIMER_IRQ, true);
+        USING_APIC_TIMER.store(true, Ordering::Relaxed);
+        apic::start_periodic_timer(TIMER_INTERRUPT_VECTOR as u8, initial_count);
+    });
+
+    Ok(())
+}
+
+pub fn use_pit_timer() {
+    without_interrupts(|| {
+        if USING_APIC_TIMER.swap(false, Ordering::Relaxed) {
+            apic::stop_timer();
+            pic::set_irq_masked(TIMER_IRQ, false);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apic_count_is_apic_ticks_per_pit_tick() {
+        assert_eq!(apic_initial_count(4_000_000, APIC_CALIBRATION_TICKS), 1_000_000);
+        assert_eq!(apic_initial_count(1_000_003, 4), 250_000);
+    }
+
+    #[test]
+    fn apic_count_never_reaches_zero() {
+        assert_eq!(apic_initial_count(0, APIC_CALIBRATION_TICKS), 1);
+        assert_eq!(apic_initial_count(3, 4), 1);
+        assert_eq!(apic_initial_count(1_000, 0), 1_000);
+    }
+
+    #[test]
+    fn ms_round_up_to_whole_ticks() {
+        assert_eq!(ms_to_ticks(0), 0);
+        assert_eq!(ms_to_ticks(1), 1);
+        assert_eq!(ms_to_ticks(54), 1);
+        assert_eq!(ms_to_ticks(55), 2);
+        assert_eq!(ms_to_ticks(1000), TICKS_PER_SECOND + 1);
+    }
+
+    #[test]
+    fn ticks_use_the_exact_pit_period() {
+        assert_eq!(ticks_to_ms(1), 54);
+        assert_eq!(ticks_to_ms(TICKS_PER_SECOND), 988);
+        // An hour of ticks lands within one tick of 3_600_000 ms
+        assert_eq!(ticks_to_ms(65_544), 3_599_975);
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        for ms in 0..5_000 {
+            assert!(ticks_to_ms(ms_to_ticks(ms)) >= ms, "{ms} ms sleeps too short");
+        }
+        for ticks in 0..1_000 {
+            assert_eq!(ms_to_ticks(ticks_to_ms(ticks)), ticks);
+        }
+    }
+
+    #[test]
+    fn zero_ms_sleep_returns_immediately() {
+        sleep_ms(0);
+    }
+}
@@ -1,50 +1,106 @@
 #![feature(abi_x86_interrupt)]
-#![no_std]
-#![no_main]
+// Unit tests run on the host with std, everything else is freestanding
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
+// Most of the kernel is only reachable from `kernel_main`, not from the tests
+#![cfg_attr(test, allow(dead_code, unused_imports))]
 
 mod memory;
 mod cpu;
 mod drivers;
 mod shell;
 mod utils;
+mod panic_screen;
 
 extern crate alloc;
 
 use core::panic::PanicInfo;
-use bootloader_api::{entry_point, BootInfo};
+use x86_64::instructions::interrupts;
+use bootloader_api::{entry_point, BootInfo, BootloaderConfig};
+use bootloader_api::config::Mapping;
 use memory::init_heap;
 use drivers::display::init_screen;
-use shell::Console;
+use drivers::input::InputEvent;
+use shell::{Clock, Console, Shell, cursor_blink_visible};
 
-entry_point!(kernel_main);
+const MAX_EVENTS_PER_ITERATION: usize = 64;
+
+// Map all physical memory so memory-mapped devices like the local APIC are reachable
+static BOOTLOADER_CONFIG: BootloaderConfig = {
+    let mut config = BootloaderConfig::new_default();
+    config.mappings.physical_memory = Some(Mapping::Dynamic);
+    config
+};
+
+#[cfg(not(test))]
+entry_point!(kernel_main, config = &BOOTLOADER_CONFIG);
 
 fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
+    let physical_memory_offset = boot_info.physical_memory_offset.as_ref().copied();
+
     cpu::init();
-    drivers::init();
+    drivers::init(physical_memory_offset);
     init_heap();
+    kprintln!("Kernel initialized");
 
     let mut screen = init_screen(boot_info);
 
-    screen.clear_screen(0xFF000000);
+    screen.clear_screen(shell::theme::background_color());
     let mut console = Console::new(screen);
+    let mut shell = Shell::new();
+    let mut clock = Clock::new();
 
     console.print("Welcome to the rust kernel\n");
-    console.print("> ");
+    shell.print_prompt(&mut console);
+
+    let mut last_input_tick = drivers::timer::ticks();
 
     loop {
-        if let Some(character) = drivers::keyboard::try_read_char() {
-            if character == '\n' {
-                console.print("\n> ");
-            } else {
-                console.print(&alloc::format!("{}", character));
+        drivers::keyboard::process_scancodes();
+
+        // Drain queued events so fast typing keeps up, but bound the batch so
+        // a flood of input cannot starve the halt below
+        for _ in 0..MAX_EVENTS_PER_ITERATION {
+            let Some(event) = drivers::input::try_pop() else {
+                break;
+            };
+
+            match event {
+                InputEvent::Key(key_event) => {
+                    shell.handle_key(&mut console, key_event);
+                    last_input_tick = drivers::timer::ticks();
+                    console.set_cursor_visible(true);
+                }
+                InputEvent::Tick => {
+                    let now = drivers::timer::ticks();
+                    console.set_cursor_visible(cursor_blink_visible(now.wrapping_sub(last_input_tick)));
+                    clock.update(&mut console, now);
+                }
             }
         }
-        core::hint::spin_loop();
+
+        halt_until_interrupt();
+    }
+}
+
+// Sleeps until the next timer tick or key press instead of spinning. Interrupts stay
+// off between the queue check and `hlt` so an event cannot slip in unnoticed.
+fn halt_until_interrupt() {
+    interrupts::disable();
+    if !drivers::input::is_empty() || drivers::keyboard::has_pending_scancodes() {
+        interrupts::enable();
+    } else {
+        interrupts::enable_and_hlt();
     }
 }
 
+#[cfg(not(test))]
 #[panic_handler]
-fn panic(_info: &PanicInfo) -> ! {
+fn panic(info: &PanicInfo) -> ! {
+    interrupts::disable();
+    drivers::serial::print_panic(format_args!("KERNEL PANIC: {}\n", info));
+    panic_screen::show(info);
+
     loop {
         core::hint::spin_loop();
     }
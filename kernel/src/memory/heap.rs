@@ -1,9 +1,18 @@
-use linked_list_allocator::LockedHeap;
+use linked_list_allocator::{Heap, LockedHeap};
+use x86_64::instructions::interrupts::without_interrupts;
 
 const MIB: usize = 1024 * 1024;
-const HEAP_SIZE: usize = 6 * MIB; // 6 MiB
+// The heap is a static array in .bss, so its size is fixed at build time.
+// Raise this if the shell starts running out of memory.
+const HEAP_SIZE: usize = 16 * MIB; // 16 MiB
 static mut HEAP_SPACE: [u8; HEAP_SIZE] = [0; HEAP_SIZE];
 
+pub struct HeapStats {
+    pub used: usize,
+    pub free: usize,
+    pub total: usize,
+}
+
 #[allow(static_mut_refs)]
 pub fn init_heap() {
     unsafe {
@@ -11,5 +20,52 @@ pub fn init_heap() {
     }
 }
 
-#[global_allocator]
+pub fn heap_stats() -> HeapStats {
+    without_interrupts(|| stats_of(&ALLOCATOR.lock()))
+}
+
+fn stats_of(heap: &Heap) -> HeapStats {
+    HeapStats {
+        used: heap.used(),
+        free: heap.free(),
+        total: heap.size(),
+    }
+}
+
+// Tests run on the host and use its allocator
+#[cfg_attr(not(test), global_allocator)]
 static ALLOCATOR: LockedHeap = LockedHeap::empty();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use core::alloc::Layout;
+
+    const TEST_HEAP_SIZE: usize = 4096;
+
+    fn test_heap() -> Heap {
+        let space = vec![0u8; TEST_HEAP_SIZE].leak();
+        let mut heap = Heap::empty();
+        unsafe { heap.init(space.as_mut_ptr(), TEST_HEAP_SIZE) };
+        heap
+    }
+
+    #[test]
+    fn allocating_and_freeing_shows_in_the_stats() {
+        let mut heap = test_heap();
+        let before = stats_of(&heap);
+        assert_eq!((before.used, before.free, before.total), (0, TEST_HEAP_SIZE, TEST_HEAP_SIZE));
+
+        let layout = Layout::from_size_align(256, 8).unwrap();
+        let block = heap.allocate_first_fit(layout).unwrap();
+        let during = stats_of(&heap);
+        assert!(during.used >= 256);
+        assert_eq!(during.used + during.free, during.total);
+        assert_eq!(during.total, TEST_HEAP_SIZE);
+
+        unsafe { heap.deallocate(block, layout) };
+        let after = stats_of(&heap);
+        assert_eq!((after.used, after.free), (0, TEST_HEAP_SIZE));
+    }
+}
@@ -1,2 +1,2 @@
 mod heap;
-pub use heap::init_heap;
+pub use heap::{init_heap, heap_stats};
@@ -0,0 +1,137 @@
+use core::fmt::{self, Write};
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, Ordering};
+use crate::drivers::display::screen::panic_screen;
+use crate::drivers::display::font::{FONT_WIDTH, FONT_HEIGHT};
+use crate::drivers::display::graphics::draw_text_scaled;
+
+const PANIC_BACKGROUND_COLOR: u32 = 0xFFAA0000;
+const PANIC_TEXT_COLOR: u32 = 0xFFFFFFFF;
+const PANIC_MARGIN: usize = 16;
+const PANIC_TITLE_SCALE: usize = 2;
+const PANIC_LINE_SPACING: usize = 4;
+const PANIC_TEXT_CAPACITY: usize = 1024;
+
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
+// Fixed-size text buffer so composing the message never touches the heap,
+// which may well be what panicked
+pub struct PanicText {
+    buffer: [u8; PANIC_TEXT_CAPACITY],
+    length: usize,
+}
+
+impl PanicText {
+    const fn new() -> Self {
+        Self {
+            buffer: [0; PANIC_TEXT_CAPACITY],
+            length: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        let bytes = &self.buffer[..self.length];
+        match core::str::from_utf8(bytes) {
+            Ok(text) => text,
+            // Truncation may have split a multi-byte character
+            Err(error) => core::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or(""),
+        }
+    }
+}
+
+impl Write for PanicText {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let available = PANIC_TEXT_CAPACITY - self.length;
+        let count = text.len().min(available);
+        self.buffer[self.length..self.length + count].copy_from_slice(&text.as_bytes()[..count]);
+        self.length += count;
+        Ok(())
+    }
+}
+
+pub fn format_panic_message(info: &PanicInfo) -> PanicText {
+    let mut text = PanicText::new();
+
+    if let Some(location) = info.location() {
+        let _ = writeln!(text, "panicked at {}:{}:{}", location.file(), location.line(), location.column());
+    }
+    let _ = write!(text, "{}", info.message());
+
+    text
+}
+
+pub fn show(info: &PanicInfo) {
+    // A panic while drawing the panic screen must not recurse
+    if PANICKING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let Some(mut screen) = (unsafe { panic_screen() }) else {
+        return;
+    };
+
+    screen.clear_screen(PANIC_BACKGROUND_COLOR);
+
+    let margin = PANIC_MARGIN as isize;
+    draw_text_scaled(&mut screen, margin, margin, "KERNEL PANIC", PANIC_TEXT_COLOR, PANIC_TITLE_SCALE);
+
+    let columns = (screen.width.saturating_sub(2 * PANIC_MARGIN) / FONT_WIDTH).max(1);
+    let line_height = (FONT_HEIGHT + PANIC_LINE_SPACING) as isize;
+    let mut y = margin + (FONT_HEIGHT * PANIC_TITLE_SCALE) as isize + 2 * line_height;
+
+    let message = format_panic_message(info);
+    for row in wrap_lines(message.as_str(), columns) {
+        draw_text_scaled(&mut screen, margin, y, row, PANIC_TEXT_COLOR, 1);
+        y += line_height;
+    }
+}
+
+// Splits each line into rows of at most `columns` characters. Empty lines
+// still take up a row.
+fn wrap_lines(text: &str, columns: usize) -> impl Iterator<Item = &str> {
+    text.lines().flat_map(move |line| {
+        let mut remaining = Some(line);
+        core::iter::from_fn(move || {
+            let current = remaining?;
+            let split = current.char_indices().nth(columns).map_or(current.len(), |(index, _)| index);
+            let (visible, rest) = current.split_at(split);
+            remaining = (!rest.is_empty()).then_some(rest);
+            Some(visible)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn wrapped(text: &str, columns: usize) -> Vec<&str> {
+        wrap_lines(text, columns).collect()
+    }
+
+    #[test]
+    fn long_lines_wrap_at_the_column_count() {
+        assert_eq!(wrapped("abcdefg", 3), ["abc", "def", "g"]);
+        assert_eq!(wrapped("abcdef", 3), ["abc", "def"]);
+        assert_eq!(wrapped("ab", 3), ["ab"]);
+    }
+
+    #[test]
+    fn each_line_starts_a_new_row() {
+        assert_eq!(wrapped("abcd\n\nef", 3), ["abc", "d", "", "ef"]);
+    }
+
+    #[test]
+    fn wrapping_counts_characters_not_bytes() {
+        assert_eq!(wrapped("äöüß", 2), ["äö", "üß"]);
+    }
+
+    #[test]
+    fn panic_text_truncates_at_capacity_on_a_character_boundary() {
+        let mut text = PanicText::new();
+        let _ = write!(text, "{}", "a".repeat(PANIC_TEXT_CAPACITY - 1));
+        let _ = write!(text, "é and more");
+        assert_eq!(text.as_str().len(), PANIC_TEXT_CAPACITY - 1);
+    }
+}
@@ -0,0 +1,97 @@
+use alloc::format;
+use alloc::string::String;
+use super::Console;
+use super::theme;
+use crate::drivers::display::font::FONT_WIDTH;
+use crate::drivers::display::graphics::{draw_filled_rect, draw_text_scaled};
+use crate::drivers::keyboard::{self, Modifiers};
+use crate::drivers::rtc::{self, DateTime};
+use crate::drivers::timer::TICKS_PER_SECOND;
+
+const CLOCK_MARGIN: usize = 4;
+const STATUS_MAX_LENGTH: usize = "CAPS NUM 00:00".len();
+
+pub fn format_clock(time: &DateTime) -> String {
+    format!("{:02}:{:02}", time.hour, time.minute)
+}
+
+// The clock, preceded by the lock keys that are on
+pub fn format_status(time: &DateTime, modifiers: &Modifiers) -> String {
+    let caps_lock = if modifiers.caps_lock { "CAPS " } else { "" };
+    let num_lock = if modifiers.num_lock { "NUM " } else { "" };
+    format!("{}{}{}", caps_lock, num_lock, format_clock(time))
+}
+
+// Time and lock key display in the top-right corner of the console's status row
+pub struct Clock {
+    last_update_tick: Option<u64>,
+    last_locks: (bool, bool),
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self {
+            last_update_tick: None,
+            last_locks: (false, false),
+        }
+    }
+
+    // Redraws once a second, or right away when a lock key is toggled
+    pub fn update(&mut self, console: &mut Console, now_ticks: u64) {
+        let modifiers = keyboard::modifiers();
+        let locks = (modifiers.caps_lock, modifiers.num_lock);
+        if let Some(last_update_tick) = self.last_update_tick
+            && now_ticks.wrapping_sub(last_update_tick) < TICKS_PER_SECOND
+            && locks == self.last_locks
+        {
+            return;
+        }
+        self.last_update_tick = Some(now_ticks);
+        self.last_locks = locks;
+
+        let text = format_status(&rtc::now(), &modifiers);
+        let scale = console.scale();
+        let width = STATUS_MAX_LENGTH * FONT_WIDTH * scale + 2 * CLOCK_MARGIN;
+        let height = console.text_top();
+        let x = console.screen.width.saturating_sub(width) as isize;
+        let text_x = console.screen.width.saturating_sub(text.len() * FONT_WIDTH * scale + CLOCK_MARGIN);
+
+        // Only the status rect is repainted
+        draw_filled_rect(&mut console.screen, x, 0, width, height, theme::background_color());
+        draw_text_scaled(
+            &mut console.screen,
+            text_x as isize,
+            0,
+            &text,
+            theme::text_color(),
+            scale,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOON: DateTime = DateTime { hour: 12, minute: 5, second: 0, day: 1, month: 1, year: 2026 };
+
+    #[test]
+    fn status_lists_the_lock_keys_that_are_on() {
+        let mut modifiers = Modifiers::new();
+        assert_eq!(format_status(&NOON, &modifiers), "12:05");
+
+        modifiers.caps_lock = true;
+        assert_eq!(format_status(&NOON, &modifiers), "CAPS 12:05");
+
+        modifiers.num_lock = true;
+        assert_eq!(format_status(&NOON, &modifiers).len(), STATUS_MAX_LENGTH);
+    }
+
+    #[test]
+    fn clock_is_zero_padded_hours_and_minutes() {
+        let time = |hour, minute| DateTime { hour, minute, ..NOON };
+        assert_eq!(format_clock(&time(0, 0)), "00:00");
+        assert_eq!(format_clock(&time(9, 7)), "09:07");
+        assert_eq!(format_clock(&time(23, 59)), "23:59");
+    }
+}
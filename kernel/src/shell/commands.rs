@@ -0,0 +1,182 @@
+use alloc::format;
+use super::Console;
+use super::gallery;
+use super::theme::{self, Theme};
+use crate::drivers::keyboard::{self, KeyboardLayout};
+use crate::drivers::{speaker, timer};
+use crate::memory::heap_stats;
+use crate::utils::random;
+
+pub const COMMANDS: [&str; 11] = [
+    "help", "layout", "font", "theme", "beep", "mem", "timer", "uptime", "random", "draw", "history",
+];
+
+const MAX_FONT_SCALE: usize = 4;
+const KIB: usize = 1024;
+const DEFAULT_BEEP_FREQUENCY_HZ: u32 = 880;
+const DEFAULT_BEEP_DURATION_MS: u64 = 200;
+const MAX_BEEP_DURATION_MS: u64 = 5000;
+
+pub fn execute(console: &mut Console, line: &str) {
+    let mut arguments = line.split_whitespace();
+    let Some(command) = arguments.next() else {
+        return;
+    };
+
+    match command {
+        "help" => help(console),
+        "layout" => layout(console, arguments.next()),
+        "font" => font(console, arguments.next()),
+        "theme" => set_theme(console, arguments.next()),
+        "beep" => beep(console, arguments.next(), arguments.next()),
+        "mem" => mem(console),
+        "timer" => timer_source(console, arguments.next()),
+        "uptime" => uptime(console),
+        "random" => random_number(console, arguments.next(), arguments.next()),
+        "draw" => gallery::draw(console),
+        _ => console.print(&format!("Unknown command: {}\n", command)),
+    }
+}
+
+fn help(console: &mut Console) {
+    console.print("Commands:");
+    for command in COMMANDS {
+        console.print(" ");
+        console.print(command);
+    }
+    console.print("\n");
+}
+
+fn layout(console: &mut Console, name: Option<&str>) {
+    let Some(name) = name else {
+        console.print(&format!("Keyboard layout: {}\n", keyboard::layout().name()));
+        print_available_layouts(console);
+        return;
+    };
+
+    match KeyboardLayout::from_name(name) {
+        Some(layout) => {
+            keyboard::set_layout(layout);
+            console.print(&format!("Keyboard layout set to {}\n", layout.name()));
+        }
+        None => {
+            console.print(&format!("Unknown layout: {}\n", name));
+            print_available_layouts(console);
+        }
+    }
+}
+
+fn print_available_layouts(console: &mut Console) {
+    console.print("Available:");
+    for layout in KeyboardLayout::ALL {
+        console.print(" ");
+        console.print(layout.name());
+    }
+    console.print("\n");
+}
+
+fn font(console: &mut Console, scale: Option<&str>) {
+    let Some(scale) = scale else {
+        console.print(&format!("Font scale: {}\n", console.scale()));
+        return;
+    };
+
+    match scale.parse::<usize>() {
+        Ok(scale) if (1..=MAX_FONT_SCALE).contains(&scale) => {
+            if let Err(error) = console.set_scale(scale) {
+                console.print(error);
+                console.print("\n");
+            }
+        }
+        _ => console.print(&format!("Font scale must be between 1 and {}\n", MAX_FONT_SCALE)),
+    }
+}
+
+fn set_theme(console: &mut Console, name: Option<&str>) {
+    let Some(name) = name else {
+        console.print(&format!("Theme: {}\n", theme::current_theme().name()));
+        return;
+    };
+
+    match Theme::from_name(name) {
+        Some(new_theme) => {
+            let previous = theme::palette();
+            theme::set_theme(new_theme);
+            console.repaint_theme(previous);
+        }
+        None => console.print(&format!("Unknown theme: {} (dark, light)\n", name)),
+    }
+}
+
+fn beep(console: &mut Console, frequency: Option<&str>, duration: Option<&str>) {
+    let frequency = frequency.map_or(Ok(DEFAULT_BEEP_FREQUENCY_HZ), str::parse::<u32>);
+    let duration = duration.map_or(Ok(DEFAULT_BEEP_DURATION_MS), str::parse::<u64>);
+
+    match (frequency, duration) {
+        (Ok(frequency), Ok(duration)) if frequency > 0 && duration <= MAX_BEEP_DURATION_MS => {
+            speaker::beep(frequency, duration);
+        }
+        _ => console.print(&format!("Usage: beep [hz] [ms <= {}]\n", MAX_BEEP_DURATION_MS)),
+    }
+}
+
+fn mem(console: &mut Console) {
+    let stats = heap_stats();
+    console.print(&format!(
+        "Heap: {} KiB used, {} KiB free, {} KiB total\n",
+        stats.used / KIB,
+        stats.free / KIB,
+        stats.total / KIB,
+    ));
+}
+
+fn timer_source(console: &mut Console, source: Option<&str>) {
+    match source {
+        None => {}
+        Some("pit") => timer::use_pit_timer(),
+        Some("apic") => {
+            if let Err(error) = timer::use_apic_timer() {
+                console.print(&format!("Cannot use APIC timer: {}\n", error));
+            }
+        }
+        Some(other) => {
+            console.print(&format!("Unknown tick source: {} (pit, apic)\n", other));
+            return;
+        }
+    }
+
+    console.print(&format!("Tick source: {}\n", timer::tick_source_name()));
+}
+
+fn uptime(console: &mut Console) {
+    let uptime_ms = timer::uptime_ms();
+    console.print(&format!("Uptime: {}.{:03} s\n", uptime_ms / 1000, uptime_ms % 1000));
+}
+
+fn random_number(console: &mut Console, first: Option<&str>, second: Option<&str>) {
+    const USAGE: &str = "Usage: random [min max] | random seed <n>\n";
+
+    let number = match (first, second) {
+        (None, None) => random::next_u64(),
+        (Some("seed"), Some(value)) => {
+            match value.parse::<u64>() {
+                Ok(value) => random::seed(value),
+                Err(_) => console.print(USAGE),
+            }
+            return;
+        }
+        (Some(min), Some(max)) => match (min.parse::<u64>(), max.parse::<u64>()) {
+            (Ok(min), Ok(max)) => random::range(min, max),
+            _ => {
+                console.print(USAGE);
+                return;
+            }
+        },
+        _ => {
+            console.print(USAGE);
+            return;
+        }
+    };
+
+    console.print(&format!("{}\n", number));
+}
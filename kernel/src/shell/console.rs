@@ -1,159 +1,317 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use crate::drivers::display::Screen;
+use crate::drivers::display::font::{FONT_WIDTH, FONT_HEIGHT};
+use crate::drivers::display::graphics::{draw_char_scaled, draw_filled_rect};
+use crate::drivers::timer::TICKS_PER_SECOND;
+use super::theme::{self, Palette};
+
+const LINE_SPACING: usize = 2;
+const HIGH_RESOLUTION_WIDTH: usize = 1600;
+const CURSOR_BLINK_INTERVAL_TICKS: u64 = TICKS_PER_SECOND / 2;
+
+// The cursor stays solid for one interval after the last keystroke and then
+// alternates, so it never disappears while the user is typing
+pub fn cursor_blink_visible(ticks_since_input: u64) -> bool {
+    (ticks_since_input / CURSOR_BLINK_INTERVAL_TICKS).is_multiple_of(2)
+}
 
 pub struct Console {
     pub screen: Screen,
     pub cursor_x: usize,
     pub cursor_y: usize,
-    pub text_color: u32
-}
-
-const FONT_WIDTH: usize = 8;
-const FONT_HEIGHT: usize = 8;
-
-fn get_char_bitmap(c: char) -> [u8; 8] {
-    match c {
-        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
-        '!' => [0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x18, 0x00],
-        ':' => [0x00, 0x18, 0x18, 0x00, 0x18, 0x18, 0x00, 0x00],
-        '>' => [0x60, 0x30, 0x18, 0x0C, 0x18, 0x30, 0x60, 0x00],
-        '<' => [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00],
-        '-' => [0x00, 0x00, 0x00, 0x7E, 0x00, 0x00, 0x00, 0x00],
-        '0' => [0x3C, 0x66, 0x66, 0x6E, 0x76, 0x66, 0x66, 0x3C],
-        '1' => [0x18, 0x38, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C],
-        '2' => [0x3C, 0x66, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x7E],
-        '3' => [0x3C, 0x66, 0x06, 0x1C, 0x06, 0x66, 0x66, 0x3C],
-        '4' => [0x0C, 0x1C, 0x3C, 0x6C, 0x7E, 0x0C, 0x0C, 0x1E],
-        '5' => [0x7E, 0x60, 0x60, 0x7C, 0x06, 0x66, 0x66, 0x3C],
-        '6' => [0x3C, 0x66, 0x60, 0x7C, 0x66, 0x66, 0x66, 0x3C],
-        '7' => [0x7E, 0x66, 0x0C, 0x0C, 0x18, 0x18, 0x30, 0x30],
-        '8' => [0x3C, 0x66, 0x66, 0x3C, 0x66, 0x66, 0x66, 0x3C],
-        '9' => [0x3C, 0x66, 0x66, 0x3E, 0x06, 0x66, 0x66, 0x3C],
-        'H' => [0x66, 0x66, 0x66, 0x7E, 0x66, 0x66, 0x66, 0x00],
-        'e' => [0x00, 0x00, 0x3C, 0x66, 0x7E, 0x60, 0x3C, 0x00],
-        'l' => [0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x0E, 0x00],
-        'o' => [0x00, 0x00, 0x3C, 0x66, 0x66, 0x66, 0x3C, 0x00],
-        'W' => [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00],
-        'r' => [0x00, 0x00, 0x6C, 0x76, 0x60, 0x60, 0x60, 0x00],
-        'd' => [0x06, 0x06, 0x3E, 0x66, 0x66, 0x66, 0x3E, 0x00],
-        'A' => [0x18, 0x3C, 0x66, 0x66, 0x7E, 0x66, 0x66, 0x00],
-        'B' => [0x7C, 0x66, 0x66, 0x7C, 0x66, 0x66, 0x7C, 0x00],
-        'C' => [0x3C, 0x66, 0x60, 0x60, 0x60, 0x66, 0x3C, 0x00],
-        'D' => [0x78, 0x6C, 0x66, 0x66, 0x66, 0x6C, 0x78, 0x00],
-        'E' => [0x7E, 0x60, 0x60, 0x7C, 0x60, 0x60, 0x7E, 0x00],
-        'F' => [0x7E, 0x60, 0x60, 0x7C, 0x60, 0x60, 0x60, 0x00],
-        'G' => [0x3C, 0x66, 0x60, 0x6E, 0x66, 0x66, 0x3C, 0x00],
-        'I' => [0x3C, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, 0x00],
-        'J' => [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x6C, 0x38, 0x00],
-        'K' => [0x66, 0x6C, 0x78, 0x70, 0x78, 0x6C, 0x66, 0x00],
-        'L' => [0x60, 0x60, 0x60, 0x60, 0x60, 0x60, 0x7E, 0x00],
-        'M' => [0x63, 0x77, 0x7F, 0x6B, 0x63, 0x63, 0x63, 0x00],
-        'N' => [0x66, 0x76, 0x7E, 0x7E, 0x6E, 0x66, 0x66, 0x00],
-        'O' => [0x3C, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3C, 0x00],
-        'P' => [0x7C, 0x66, 0x66, 0x7C, 0x60, 0x60, 0x60, 0x00],
-        'Q' => [0x3C, 0x66, 0x66, 0x66, 0x66, 0x3C, 0x0E, 0x00],
-        'R' => [0x7C, 0x66, 0x66, 0x7C, 0x78, 0x6C, 0x66, 0x00],
-        'S' => [0x3C, 0x66, 0x60, 0x3C, 0x06, 0x66, 0x3C, 0x00],
-        'T' => [0x7E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00],
-        'U' => [0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x3C, 0x00],
-        'V' => [0x66, 0x66, 0x66, 0x66, 0x66, 0x3C, 0x18, 0x00],
-        'X' => [0x66, 0x66, 0x3C, 0x18, 0x3C, 0x66, 0x66, 0x00],
-        'Y' => [0x66, 0x66, 0x66, 0x3C, 0x18, 0x18, 0x18, 0x00],
-        'Z' => [0x7E, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x7E, 0x00],
-        'a' => [0x00, 0x00, 0x3C, 0x06, 0x3E, 0x66, 0x3E, 0x00],
-        'b' => [0x60, 0x60, 0x7C, 0x66, 0x66, 0x66, 0x7C, 0x00],
-        'c' => [0x00, 0x00, 0x3C, 0x60, 0x60, 0x60, 0x3C, 0x00],
-        'f' => [0x0E, 0x18, 0x18, 0x3E, 0x18, 0x18, 0x18, 0x00],
-        'g' => [0x00, 0x00, 0x3E, 0x66, 0x66, 0x3E, 0x06, 0x3C],
-        'h' => [0x60, 0x60, 0x7C, 0x66, 0x66, 0x66, 0x66, 0x00],
-        'i' => [0x18, 0x00, 0x38, 0x18, 0x18, 0x18, 0x3C, 0x00],
-        'j' => [0x06, 0x00, 0x06, 0x06, 0x06, 0x06, 0x66, 0x3C],
-        'k' => [0x60, 0x60, 0x66, 0x6C, 0x78, 0x6C, 0x66, 0x00],
-        'm' => [0x00, 0x00, 0x66, 0x7F, 0x7F, 0x6B, 0x63, 0x00],
-        'n' => [0x00, 0x00, 0x7C, 0x66, 0x66, 0x66, 0x66, 0x00],
-        'p' => [0x00, 0x00, 0x7C, 0x66, 0x66, 0x7C, 0x60, 0x60],
-        'q' => [0x00, 0x00, 0x3E, 0x66, 0x66, 0x3E, 0x06, 0x06],
-        's' => [0x00, 0x00, 0x3E, 0x60, 0x3C, 0x06, 0x7C, 0x00],
-        't' => [0x18, 0x18, 0x7E, 0x18, 0x18, 0x18, 0x0E, 0x00],
-        'u' => [0x00, 0x00, 0x66, 0x66, 0x66, 0x66, 0x3E, 0x00],
-        'v' => [0x00, 0x00, 0x66, 0x66, 0x66, 0x3C, 0x18, 0x00],
-        'w' => [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x3E, 0x36, 0x00],
-        'x' => [0x00, 0x00, 0x66, 0x3C, 0x18, 0x3C, 0x66, 0x00],
-        'y' => [0x00, 0x00, 0x66, 0x66, 0x66, 0x3E, 0x0C, 0x78],
-        'z' => [0x00, 0x00, 0x7E, 0x0C, 0x18, 0x30, 0x7E, 0x00],
-        _ => [0xFF, 0x81, 0x81, 0x81, 0x81, 0x81, 0x81, 0xFF],
-    }
+    scale: usize,
+    cursor_visible: bool,
+    // Characters on screen, row by row, so the text can be redrawn
+    cells: Vec<char>,
+    columns: usize,
 }
 
 impl Console {
     pub fn new(screen: Screen) -> Self {
-        let console = Console { 
+        // The 8x8 font is barely readable on large framebuffers, so double it there
+        let scale = if screen.width >= HIGH_RESOLUTION_WIDTH { 2 } else { 1 };
+
+        let mut console = Console { 
             screen,
             cursor_x: 0,
             cursor_y: 0,
-            text_color: 0xFFFFFFFF
+            scale,
+            cursor_visible: false,
+            cells: Vec::new(),
+            columns: 0,
         };
+        console.cursor_y = console.text_top();
+        console.reset_cells();
         
         return console;
     }
 
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    // Refuses scales at which not even one character fits on the screen
+    pub fn set_scale(&mut self, scale: usize) -> Result<(), &'static str> {
+        let scale = scale.max(1);
+        let line_height = (FONT_HEIGHT + LINE_SPACING) * scale;
+        if FONT_WIDTH * scale > self.screen.width || 2 * line_height > self.screen.height {
+            return Err("Font scale too large for this screen");
+        }
+
+        self.scale = scale;
+        self.clear();
+        Ok(())
+    }
+
+    pub fn char_width(&self) -> usize {
+        FONT_WIDTH * self.scale
+    }
+
+    pub fn line_height(&self) -> usize {
+        (FONT_HEIGHT + LINE_SPACING) * self.scale
+    }
+
+    // The first line is a status row for the clock; text never reaches it
+    pub fn text_top(&self) -> usize {
+        self.line_height()
+    }
+
+    fn rows(&self) -> usize {
+        self.screen.height.saturating_sub(self.text_top()) / self.line_height()
+    }
+
+    fn reset_cells(&mut self) {
+        self.columns = self.screen.width / self.char_width();
+        self.cells = vec![' '; self.columns * self.rows()];
+    }
+
+    fn cell_index(&self, x: usize, y: usize) -> Option<usize> {
+        let column = x / self.char_width();
+        let row = y.checked_sub(self.text_top())? / self.line_height();
+        (column < self.columns).then_some(row * self.columns + column)
+    }
+
+    pub fn clear(&mut self) {
+        self.cursor_visible = false;
+        self.screen.clear_screen(theme::background_color());
+        self.cursor_x = 0;
+        self.cursor_y = self.text_top();
+        self.reset_cells();
+    }
+
+    // Redraws the whole screen from the cell buffer in the current palette.
+    // The clock repaints itself on its next update.
+    pub fn repaint_theme(&mut self, previous: Palette) {
+        let current = theme::palette();
+        if current == previous {
+            return;
+        }
+
+        self.cursor_visible = false;
+        self.screen.clear_screen(current.background);
+
+        let (top, cell_width, cell_height) = (self.text_top(), self.char_width(), self.line_height());
+        for (index, &c) in self.cells.iter().enumerate() {
+            if c == ' ' {
+                continue;
+            }
+            let x = (index % self.columns * cell_width) as isize;
+            let y = (top + index / self.columns * cell_height) as isize;
+            draw_char_scaled(&mut self.screen, x, y, c, current.text, self.scale);
+        }
+    }
+
     pub fn print(&mut self, text: &str) {
         for c in text.chars() {
             self.print_char(c);
         }
     }
 
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        if self.cursor_visible == visible {
+            return;
+        }
+        self.cursor_visible = visible;
+
+        // Only the cursor cell is repainted on a phase change
+        let color = if visible { theme::text_color() } else { theme::background_color() };
+        let (x, y) = (self.cursor_x as isize, self.cursor_y as isize);
+        let (cursor_width, cursor_height) = (self.char_width(), FONT_HEIGHT * self.scale);
+        draw_filled_rect(&mut self.screen, x, y, cursor_width, cursor_height, color);
+    }
+
     pub fn print_char(&mut self, c: char) {
+        self.set_cursor_visible(false);
+
         if c == '\n' {
-            self.cursor_x = 0;
-            self.cursor_y += FONT_HEIGHT + 2;
-            
-            if self.cursor_y + FONT_HEIGHT > self.screen.height {
-                self.scroll_up();
-            }
+            self.new_line();
             return;
         }
 
-        if self.cursor_x + FONT_WIDTH > self.screen.width {
-            self.cursor_x = 0;
-            self.cursor_y += FONT_HEIGHT + 2;
-            
-            if self.cursor_y + FONT_HEIGHT > self.screen.height {
-                self.scroll_up();
-            }
+        if self.cursor_x + self.char_width() > self.screen.width {
+            self.new_line();
         }
 
-        let bitmap = get_char_bitmap(c);
-        
-        for row in 0..FONT_HEIGHT {
-            let row_data = bitmap[row];
-            for col in 0..FONT_WIDTH {
-                let pixel_on = (row_data >> (7 - col)) & 1 == 1;
-                let color = if pixel_on { self.text_color } else { 0x000000 };
-                let x = self.cursor_x + col;
-                let y = self.cursor_y + row;
-                if x < self.screen.width && y < self.screen.height {
-                    self.screen.write_pixel(x, y, color);
-                }
-            }
+        self.draw_cell(c);
+        self.cursor_x += self.char_width();
+    }
+
+    // Replaces the cell under the cursor without moving it
+    fn draw_cell(&mut self, c: char) {
+        let (x, y) = (self.cursor_x as isize, self.cursor_y as isize);
+        let (cell_width, cell_height) = (self.char_width(), self.line_height());
+        draw_filled_rect(&mut self.screen, x, y, cell_width, cell_height, theme::background_color());
+        draw_char_scaled(&mut self.screen, x, y, c, theme::text_color(), self.scale);
+
+        if let Some(index) = self.cell_index(self.cursor_x, self.cursor_y)
+            && let Some(cell) = self.cells.get_mut(index)
+        {
+            *cell = c;
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.cursor_x = 0;
+        self.cursor_y += self.line_height();
+
+        if self.cursor_y + self.line_height() > self.screen.height {
+            self.scroll_up();
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.set_cursor_visible(false);
+
+        if self.cursor_x >= self.char_width() {
+            self.cursor_x -= self.char_width();
+        } else if self.cursor_y >= self.text_top() + self.line_height() {
+            // Step back onto the last column of the previous (wrapped) line
+            self.cursor_y -= self.line_height();
+            self.cursor_x = self.columns.saturating_sub(1) * self.char_width();
+        } else {
+            return;
         }
 
-        self.cursor_x += FONT_WIDTH;
+        self.draw_cell(' ');
     }
 
     fn scroll_up(&mut self) {
-        let scroll_distance = FONT_HEIGHT + 2;
-        let scroll_bytes = scroll_distance * self.screen.stride * self.screen.bytes_per_pixel;
-        
-        if scroll_bytes <= self.screen.framebuffer.len() {
-            for i in 0..(self.screen.framebuffer.len() - scroll_bytes) {
-                self.screen.framebuffer[i] = self.screen.framebuffer[i + scroll_bytes];
-            }
-            
-            for i in (self.screen.framebuffer.len() - scroll_bytes)..self.screen.framebuffer.len() {
-                self.screen.framebuffer[i] = 0x00;
-            }
+        let scroll_distance = self.line_height();
+        let (top, width, height) = (self.text_top(), self.screen.width, self.screen.height);
+        let moved_height = height.saturating_sub(top + scroll_distance);
+        self.screen.copy_rect(0, top + scroll_distance, width, moved_height, 0, top);
+
+        let cleared_top = height.saturating_sub(scroll_distance);
+        draw_filled_rect(&mut self.screen, 0, cleared_top as isize, width, scroll_distance, theme::background_color());
+
+        let columns = self.columns;
+        if self.cells.len() >= columns {
+            self.cells.copy_within(columns.., 0);
+            let last_row = self.cells.len() - columns;
+            self.cells[last_row..].fill(' ');
         }
         
         self.cursor_y = self.cursor_y.saturating_sub(scroll_distance);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::theme::Theme;
+
+    // The theme is global, so tests that check colors take turns
+    static THEME_USERS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn set_scale_refuses_scales_that_do_not_fit() {
+        let mut console = Console::new(Screen::in_memory(40, 60));
+
+        assert!(console.set_scale(2).is_ok());
+        assert_eq!(console.scale(), 2);
+        assert!(console.set_scale(6).is_err());
+        assert_eq!(console.scale(), 2);
+    }
+
+    #[test]
+    fn backspace_steps_back_onto_the_last_column_of_the_previous_line() {
+        let mut console = Console::new(Screen::in_memory(20, 60));
+        console.print("abc");
+        assert_eq!((console.cursor_x, console.cursor_y), (FONT_WIDTH, console.text_top() + console.line_height()));
+
+        console.backspace();
+        console.backspace();
+        assert_eq!((console.cursor_x, console.cursor_y), (FONT_WIDTH, console.text_top()));
+    }
+
+    #[test]
+    fn cursor_is_solid_for_one_interval_then_alternates() {
+        let interval = CURSOR_BLINK_INTERVAL_TICKS;
+        assert!(cursor_blink_visible(0));
+        assert!(cursor_blink_visible(interval - 1));
+        assert!(!cursor_blink_visible(interval));
+        assert!(!cursor_blink_visible(2 * interval - 1));
+        assert!(cursor_blink_visible(2 * interval));
+        assert!(!cursor_blink_visible(3 * interval));
+    }
+
+    #[test]
+    fn cursor_phase_changes_repaint_the_cursor_cell() {
+        let _theme = THEME_USERS.lock().unwrap();
+        let mut console = Console::new(Screen::in_memory(40, 60));
+        let (x, y) = (console.cursor_x, console.cursor_y);
+
+        console.set_cursor_visible(true);
+        assert_eq!(console.screen.read_pixel(x, y), Some(theme::text_color()));
+        assert_eq!(console.screen.read_pixel(x + FONT_WIDTH, y), Some(theme::background_color()));
+
+        console.set_cursor_visible(false);
+        assert_eq!(console.screen.read_pixel(x, y), Some(theme::background_color()));
+    }
+
+    #[test]
+    fn cell_index_maps_text_positions_row_by_row() {
+        let console = Console::new(Screen::in_memory(40, 60));
+        let (top, width, height) = (console.text_top(), console.char_width(), console.line_height());
+
+        assert_eq!(console.cell_index(0, top), Some(0));
+        assert_eq!(console.cell_index(width * 2 + 1, top + height + 1), Some(console.columns + 2));
+        assert_eq!(console.cell_index(0, top - 1), None);
+        assert_eq!(console.cell_index(width * console.columns, top), None);
+    }
+
+    #[test]
+    fn scrolling_moves_cells_up_one_row() {
+        let mut console = Console::new(Screen::in_memory(40, 60));
+        console.print("a\nb\nc\nd\ne\nf");
+
+        let column_zero: Vec<char> = (0..console.rows()).map(|row| console.cells[row * console.columns]).collect();
+        assert_eq!(column_zero, ['b', 'c', 'd', 'e', 'f']);
+        assert!(console.cells[1..console.columns].iter().all(|&c| c == ' '));
+    }
+
+    #[test]
+    fn theme_switch_repaints_text_from_the_cells() {
+        let _theme = THEME_USERS.lock().unwrap();
+        theme::set_theme(Theme::Dark);
+        let mut console = Console::new(Screen::in_memory(40, 60));
+        console.print("A");
+
+        let previous = theme::palette();
+        theme::set_theme(Theme::Light);
+        console.repaint_theme(previous);
+        let light = Theme::Light.palette();
+        theme::set_theme(Theme::Dark);
+
+        let top = console.text_top();
+        let glyph: Vec<u32> = (top..top + FONT_HEIGHT)
+            .flat_map(|y| (0..FONT_WIDTH).map(move |x| (x, y)))
+            .filter_map(|(x, y)| console.screen.read_pixel(x, y))
+            .collect();
+        assert!(glyph.contains(&light.text));
+        assert!(glyph.iter().all(|&pixel| pixel == light.text || pixel == light.background));
+        assert_eq!(console.screen.read_pixel(FONT_WIDTH * 2, top), Some(light.background));
+        assert_eq!(console.screen.read_pixel(0, 0), Some(light.background));
+    }
+}
@@ -0,0 +1,68 @@
+use super::Console;
+use super::theme;
+use crate::drivers::display::graphics::{
+    draw_circle, draw_filled_circle, draw_filled_rect_alpha, draw_filled_rounded_rect, draw_line,
+    draw_rounded_rect,
+};
+use crate::utils::icons::house::get_house_icon;
+use crate::utils::icons::swatch::get_swatch_icon;
+
+const CANVAS_HEIGHT: usize = 64;
+const CANVAS_MARGIN: usize = 8;
+const ICON_SIZE: usize = 24;
+const CHART_WIDTH: isize = 96;
+const CHART_POINTS: [isize; 7] = [40, 12, 28, 4, 36, 20, 44];
+const CIRCLE_RADIUS: usize = 12;
+const BUTTON_WIDTH: usize = 48;
+const BUTTON_HEIGHT: usize = 24;
+const BUTTON_RADIUS: usize = 8;
+// Translucent orange, blended over whatever is already drawn
+const HIGHLIGHT_COLOR: u32 = 0x60FF8800;
+
+// Shows off the drawing primitives in blank lines reserved below the prompt,
+// so the picture scrolls away with the rest of the output
+pub fn draw(console: &mut Console) {
+    let top = reserve_canvas(console);
+    let left = CANVAS_MARGIN;
+
+    console.screen.draw_bitmap(left, top + CANVAS_MARGIN, get_house_icon());
+    console.screen.draw_bitmap(left, top + 2 * CANVAS_MARGIN + ICON_SIZE, get_swatch_icon());
+
+    // A small line chart with an axis
+    let (chart_left, chart_top) = ((left + ICON_SIZE + CANVAS_MARGIN) as isize, top as isize);
+    let color = theme::text_color();
+    let chart_bottom = chart_top + CANVAS_HEIGHT as isize - 1;
+    draw_line(&mut console.screen, chart_left, chart_bottom, chart_left + CHART_WIDTH, chart_bottom, color);
+    let step = CHART_WIDTH / (CHART_POINTS.len() as isize - 1);
+    for (index, pair) in CHART_POINTS.windows(2).enumerate() {
+        let x = chart_left + index as isize * step;
+        draw_line(&mut console.screen, x, chart_top + pair[0], x + step, chart_top + pair[1], color);
+    }
+
+    // Filled and outlined circles stacked in one column
+    let radius = CIRCLE_RADIUS as isize;
+    let circle_x = chart_left + CHART_WIDTH + CANVAS_MARGIN as isize + radius;
+    draw_filled_circle(&mut console.screen, circle_x, chart_top + radius + 2, CIRCLE_RADIUS, color);
+    draw_circle(&mut console.screen, circle_x, chart_bottom - radius - 2, CIRCLE_RADIUS, color);
+
+    // And a pair of rounded buttons next to them
+    let button_x = circle_x + radius + CANVAS_MARGIN as isize;
+    let button_bottom = chart_bottom - BUTTON_HEIGHT as isize - 2;
+    draw_filled_rounded_rect(&mut console.screen, button_x, chart_top + 2, BUTTON_WIDTH, BUTTON_HEIGHT, BUTTON_RADIUS, color);
+    draw_rounded_rect(&mut console.screen, button_x, button_bottom, BUTTON_WIDTH, BUTTON_HEIGHT, BUTTON_RADIUS, color);
+
+    // Highlight the middle of the chart and one of the circles
+    let highlight_x = chart_left + CHART_WIDTH / 3;
+    let highlight_width = (circle_x - highlight_x) as usize;
+    draw_filled_rect_alpha(&mut console.screen, highlight_x, chart_top, highlight_width, CANVAS_HEIGHT / 2, HIGHLIGHT_COLOR);
+}
+
+// Prints enough empty lines to hold the canvas and returns its top edge
+fn reserve_canvas(console: &mut Console) -> usize {
+    let lines = CANVAS_HEIGHT.div_ceil(console.line_height());
+    for _ in 0..lines {
+        console.print("\n");
+    }
+
+    console.cursor_y.saturating_sub(lines * console.line_height()).max(console.text_top())
+}
@@ -0,0 +1,347 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use super::Console;
+use super::commands;
+use crate::drivers::keyboard::{Key, KeyEvent};
+
+const PROMPT: &str = "> ";
+const MAX_HISTORY_ENTRIES: usize = 64;
+const MAX_HISTORY_SHOWN: usize = 16;
+
+pub struct Shell {
+    input: String,
+    // Set after a Tab that could not complete further, so a second Tab lists the candidates
+    completion_pending: bool,
+    history: Vec<String>,
+    // Entries dropped from the front of `history`, so entry numbers stay stable
+    dropped_history_entries: usize,
+    // Index into `history` while recalling entries, None while editing a new line
+    history_position: Option<usize>,
+}
+
+impl Shell {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            completion_pending: false,
+            history: Vec::new(),
+            dropped_history_entries: 0,
+            history_position: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, console: &mut Console, event: KeyEvent) {
+        match event.key {
+            Key::Character(character) => self.handle_char(console, character),
+            Key::ArrowUp => self.recall_previous(console),
+            Key::ArrowDown => self.recall_next(console),
+            _ => {}
+        }
+    }
+
+    pub fn print_prompt(&self, console: &mut Console) {
+        console.print(PROMPT);
+    }
+
+    fn handle_char(&mut self, console: &mut Console, character: char) {
+        if character == '\t' {
+            self.complete(console);
+            return;
+        }
+        self.completion_pending = false;
+
+        match character {
+            '\n' => {
+                console.print("\n");
+                self.run_line(console);
+                self.input.clear();
+                self.print_prompt(console);
+            }
+            '\u{8}' => {
+                if self.input.pop().is_some() {
+                    console.backspace();
+                }
+            }
+            _ => {
+                self.input.push(character);
+                console.print_char(character);
+            }
+        }
+    }
+
+    // Completes the command name against the known commands
+    fn complete(&mut self, console: &mut Console) {
+        if self.input.contains(' ') {
+            return;
+        }
+
+        let candidates: Vec<&str> = commands::COMMANDS
+            .into_iter()
+            .filter(|command| command.starts_with(self.input.as_str()))
+            .collect();
+        let Some(prefix) = common_prefix(&candidates) else {
+            return;
+        };
+
+        if candidates.len() == 1 {
+            self.type_text(console, &prefix[self.input.len()..]);
+            self.type_text(console, " ");
+        } else if prefix.len() > self.input.len() {
+            self.type_text(console, &prefix[self.input.len()..]);
+        } else if self.completion_pending {
+            console.print("\n");
+            console.print(&candidates.join("  "));
+            console.print("\n");
+            self.print_prompt(console);
+            console.print(&self.input);
+        } else {
+            self.completion_pending = true;
+        }
+    }
+
+    fn run_line(&mut self, console: &mut Console) {
+        self.history_position = None;
+
+        let line = match self.expand_history_reference(&self.input) {
+            Ok(line) => line,
+            Err(error) => {
+                console.print(error);
+                console.print("\n");
+                return;
+            }
+        };
+        // Show what a !n or !! reference expanded to
+        if line != self.input {
+            console.print(&line);
+            console.print("\n");
+        }
+
+        // `history` needs the shell's state, so it is handled here rather than in commands
+        if line.split_whitespace().next() == Some("history") {
+            self.print_history(console);
+        } else {
+            commands::execute(console, &line);
+        }
+        self.add_to_history(line);
+    }
+
+    fn expand_history_reference(&self, line: &str) -> Result<String, &'static str> {
+        let Some(reference) = line.trim().strip_prefix('!') else {
+            return Ok(line.into());
+        };
+
+        let entry = if reference == "!" {
+            self.history.last()
+        } else {
+            let number = reference.parse::<usize>().map_err(|_| "Usage: !! or !<n>")?;
+            number
+                .checked_sub(self.dropped_history_entries + 1)
+                .and_then(|index| self.history.get(index))
+        };
+
+        entry.cloned().ok_or("No such history entry")
+    }
+
+    fn print_history(&self, console: &mut Console) {
+        let first_shown = self.history.len().saturating_sub(MAX_HISTORY_SHOWN);
+        for (index, entry) in self.history.iter().enumerate().skip(first_shown) {
+            let number = self.dropped_history_entries + index + 1;
+            console.print(&format!("{:>4}  {}\n", number, entry));
+        }
+    }
+
+    fn add_to_history(&mut self, line: String) {
+        if line.trim().is_empty() || self.history.last() == Some(&line) {
+            return;
+        }
+
+        if self.history.len() == MAX_HISTORY_ENTRIES {
+            self.history.remove(0);
+            self.dropped_history_entries += 1;
+        }
+        self.history.push(line);
+    }
+
+    // Going up past the oldest entry stays on it
+    fn recall_previous(&mut self, console: &mut Console) {
+        let position = match self.history_position {
+            Some(position) => position.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+        };
+
+        self.history_position = Some(position);
+        self.replace_input(console, self.history[position].clone());
+    }
+
+    // Going down past the newest entry clears the input
+    fn recall_next(&mut self, console: &mut Console) {
+        let Some(position) = self.history_position else {
+            return;
+        };
+
+        if position + 1 < self.history.len() {
+            self.history_position = Some(position + 1);
+            self.replace_input(console, self.history[position + 1].clone());
+        } else {
+            self.history_position = None;
+            self.replace_input(console, String::new());
+        }
+    }
+
+    fn replace_input(&mut self, console: &mut Console, text: String) {
+        for _ in self.input.chars() {
+            console.backspace();
+        }
+        console.print(&text);
+        self.input = text;
+    }
+
+    fn type_text(&mut self, console: &mut Console, text: &str) {
+        self.input.push_str(text);
+        console.print(text);
+    }
+}
+
+fn common_prefix<'a>(candidates: &[&'a str]) -> Option<&'a str> {
+    let (first, rest) = candidates.split_first()?;
+    let length = rest.iter().fold(first.len(), |length, candidate| {
+        first
+            .bytes()
+            .zip(candidate.bytes())
+            .take(length)
+            .take_while(|(a, b)| a == b)
+            .count()
+    });
+
+    Some(&first[..length])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::display::Screen;
+
+    fn console() -> Console {
+        Console::new(Screen::in_memory(320, 200))
+    }
+
+    fn type_keys(shell: &mut Shell, console: &mut Console, text: &str) {
+        for character in text.chars() {
+            let event = KeyEvent { key: Key::Character(character), shift: false, ctrl: false, alt: false };
+            shell.handle_key(console, event);
+        }
+    }
+
+    #[test]
+    fn common_prefix_of_candidates() {
+        assert_eq!(common_prefix(&[]), None);
+        assert_eq!(common_prefix(&["timer"]), Some("timer"));
+        assert_eq!(common_prefix(&["theme", "timer"]), Some("t"));
+        assert_eq!(common_prefix(&["help", "history", "hex"]), Some("h"));
+        assert_eq!(common_prefix(&["layout", "mem"]), Some(""));
+        assert_eq!(common_prefix(&["uptime", "up"]), Some("up"));
+    }
+
+    #[test]
+    fn tab_completes_a_unique_command() {
+        let (mut shell, mut console) = (Shell::new(), console());
+        type_keys(&mut shell, &mut console, "ti\t");
+        assert_eq!(shell.input, "timer ");
+    }
+
+    #[test]
+    fn tab_stops_at_an_ambiguous_prefix() {
+        let (mut shell, mut console) = (Shell::new(), console());
+        type_keys(&mut shell, &mut console, "h\t");
+        assert_eq!(shell.input, "h");
+        assert!(shell.completion_pending);
+
+        // The second Tab only lists the candidates
+        type_keys(&mut shell, &mut console, "\t");
+        assert_eq!(shell.input, "h");
+
+        type_keys(&mut shell, &mut console, "i\t");
+        assert_eq!(shell.input, "history ");
+        assert!(!shell.completion_pending);
+    }
+
+    #[test]
+    fn tab_leaves_arguments_alone() {
+        let (mut shell, mut console) = (Shell::new(), console());
+        type_keys(&mut shell, &mut console, "theme d\t");
+        assert_eq!(shell.input, "theme d");
+    }
+
+    fn shell_with_history(lines: &[&str]) -> Shell {
+        let mut shell = Shell::new();
+        for line in lines {
+            shell.add_to_history((*line).into());
+        }
+        shell
+    }
+
+    #[test]
+    fn history_skips_blank_lines_and_repeats() {
+        let shell = shell_with_history(&["help", "help", "  ", "mem", "help"]);
+        assert_eq!(shell.history, ["help", "mem", "help"]);
+    }
+
+    #[test]
+    fn bang_references_expand_to_history_entries() {
+        let shell = shell_with_history(&["help", "mem", "uptime"]);
+        assert_eq!(shell.expand_history_reference("!!"), Ok("uptime".into()));
+        assert_eq!(shell.expand_history_reference("!1"), Ok("help".into()));
+        assert_eq!(shell.expand_history_reference(" !2 "), Ok("mem".into()));
+        assert_eq!(shell.expand_history_reference("!4"), Err("No such history entry"));
+        assert_eq!(shell.expand_history_reference("!0"), Err("No such history entry"));
+        assert_eq!(shell.expand_history_reference("!x"), Err("Usage: !! or !<n>"));
+        assert_eq!(shell.expand_history_reference("mem"), Ok("mem".into()));
+    }
+
+    #[test]
+    fn bang_bang_without_history_is_an_error() {
+        assert_eq!(Shell::new().expand_history_reference("!!"), Err("No such history entry"));
+    }
+
+    #[test]
+    fn history_is_capped_and_keeps_entry_numbers() {
+        let mut shell = Shell::new();
+        for number in 1..=MAX_HISTORY_ENTRIES + 2 {
+            shell.add_to_history(format!("echo {}", number));
+        }
+
+        assert_eq!(shell.history.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(shell.dropped_history_entries, 2);
+        assert_eq!(shell.expand_history_reference("!2"), Err("No such history entry"));
+        assert_eq!(shell.expand_history_reference("!3"), Ok("echo 3".into()));
+        assert_eq!(shell.expand_history_reference("!66"), Ok("echo 66".into()));
+    }
+
+    #[test]
+    fn entered_lines_are_recorded_expanded() {
+        let (mut shell, mut console) = (Shell::new(), console());
+        type_keys(&mut shell, &mut console, "help\n!!\n!1\n");
+        assert_eq!(shell.history, ["help"]);
+        assert!(shell.input.is_empty());
+    }
+
+    #[test]
+    fn arrows_walk_through_history() {
+        let (mut shell, mut console) = (shell_with_history(&["help", "mem", "uptime"]), console());
+        let mut press = |shell: &mut Shell, key| {
+            shell.handle_key(&mut console, KeyEvent { key, shift: false, ctrl: false, alt: false });
+            shell.input.clone()
+        };
+
+        assert_eq!(press(&mut shell, Key::ArrowUp), "uptime");
+        assert_eq!(press(&mut shell, Key::ArrowUp), "mem");
+        assert_eq!(press(&mut shell, Key::ArrowUp), "help");
+        assert_eq!(press(&mut shell, Key::ArrowUp), "help");
+        assert_eq!(press(&mut shell, Key::ArrowDown), "mem");
+        assert_eq!(press(&mut shell, Key::ArrowDown), "uptime");
+        assert_eq!(press(&mut shell, Key::ArrowDown), "");
+        assert_eq!(press(&mut shell, Key::ArrowDown), "");
+    }
+}
@@ -1,2 +1,9 @@
 mod console;
-pub use console::Console;
+mod clock;
+mod commands;
+mod gallery;
+mod interpreter;
+pub mod theme;
+pub use console::{Console, cursor_blink_visible};
+pub use interpreter::Shell;
+pub use clock::Clock;
@@ -0,0 +1,63 @@
+use spin::Mutex;
+
+static ACTIVE_THEME: Mutex<Theme> = Mutex::new(Theme::Dark);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub text: u32,
+    pub background: u32,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::Dark, Theme::Light];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|theme| theme.name() == name)
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Dark => Palette {
+                text: 0xFFFFFFFF,
+                background: 0xFF000000,
+            },
+            Theme::Light => Palette {
+                text: 0xFF202020,
+                background: 0xFFF0F0F0,
+            },
+        }
+    }
+}
+
+pub fn set_theme(theme: Theme) {
+    *ACTIVE_THEME.lock() = theme;
+}
+
+pub fn current_theme() -> Theme {
+    *ACTIVE_THEME.lock()
+}
+
+pub fn palette() -> Palette {
+    current_theme().palette()
+}
+
+pub fn text_color() -> u32 {
+    palette().text
+}
+
+pub fn background_color() -> u32 {
+    palette().background
+}
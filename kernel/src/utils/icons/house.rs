@@ -1,33 +1,44 @@
+use spin::Lazy;
 use crate::drivers::display::bitmap::Bitmap;
+use super::rle::{decode_icon, IconRun};
 
-pub fn get_house_icon() -> Bitmap {
-    return Bitmap {
-        width: 24,
-        height: 24,
-        pixels: alloc::vec![
-            0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF, 0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF, 0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF, 0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF, 0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000, 0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF, 0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF, 0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF, 0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF, 0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF, 0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0xFFFFFFFF,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,
-            0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000, 0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,0x00000000,
-        ],
-    };
-}
\ No newline at end of file
+const HOUSE_ICON_SIZE: usize = 24;
+
+const HOUSE_ICON_RUNS: &[IconRun] = &[
+    (34, 0x00000000), (4, 0xFFFFFFFF), (19, 0x00000000), (6, 0xFFFFFFFF), (17, 0x00000000), (4, 0xFFFFFFFF),
+    (1, 0x00000000), (3, 0xFFFFFFFF), (14, 0x00000000), (4, 0xFFFFFFFF), (4, 0x00000000), (4, 0xFFFFFFFF),
+    (11, 0x00000000), (4, 0xFFFFFFFF), (6, 0x00000000), (5, 0xFFFFFFFF), (8, 0x00000000), (4, 0xFFFFFFFF),
+    (8, 0x00000000), (4, 0xFFFFFFFF), (7, 0x00000000), (4, 0xFFFFFFFF), (10, 0x00000000), (4, 0xFFFFFFFF),
+    (5, 0x00000000), (4, 0xFFFFFFFF), (13, 0x00000000), (3, 0xFFFFFFFF), (4, 0x00000000), (3, 0xFFFFFFFF),
+    (14, 0x00000000), (3, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF), (16, 0x00000000), (2, 0xFFFFFFFF),
+    (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (8, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF),
+    (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (8, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF),
+    (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF),
+    (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF),
+    (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF),
+    (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF),
+    (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF),
+    (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF),
+    (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (3, 0xFFFFFFFF),
+    (3, 0x00000000), (2, 0xFFFFFFFF), (4, 0x00000000), (2, 0xFFFFFFFF), (3, 0x00000000), (3, 0xFFFFFFFF),
+    (4, 0x00000000), (20, 0xFFFFFFFF), (5, 0x00000000), (18, 0xFFFFFFFF), (75, 0x00000000),
+];
+
+static HOUSE_ICON: Lazy<Bitmap> = Lazy::new(|| decode_icon(HOUSE_ICON_SIZE, HOUSE_ICON_SIZE, HOUSE_ICON_RUNS));
+
+pub fn get_house_icon() -> &'static Bitmap {
+    &HOUSE_ICON
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::icons::rle::encode_icon;
+
+    #[test]
+    fn house_icon_table_is_exactly_one_icon_of_runs() {
+        let icon = get_house_icon();
+        assert_eq!(icon.pixels.len(), HOUSE_ICON_SIZE * HOUSE_ICON_SIZE);
+        assert_eq!(encode_icon(icon), HOUSE_ICON_RUNS);
+    }
+}
@@ -1 +1,3 @@
 pub mod house;
+pub mod rle;
+pub mod swatch;
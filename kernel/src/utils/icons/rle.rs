@@ -0,0 +1,57 @@
+use alloc::vec::Vec;
+use crate::drivers::display::bitmap::Bitmap;
+
+// A run of `count` identical pixels. Icons are mostly large transparent or
+// solid areas, so this keeps the embedded tables a fraction of the raw size.
+pub type IconRun = (u16, u32);
+
+// Only used to produce and check the embedded tables, never at runtime
+#[cfg(test)]
+pub fn encode_icon(bitmap: &Bitmap) -> Vec<IconRun> {
+    let mut runs: Vec<IconRun> = Vec::new();
+
+    for &pixel in &bitmap.pixels {
+        match runs.last_mut() {
+            Some((count, color)) if *color == pixel && *count < u16::MAX => *count += 1,
+            _ => runs.push((1, pixel)),
+        }
+    }
+
+    runs
+}
+
+pub fn decode_icon(width: usize, height: usize, runs: &[IconRun]) -> Bitmap {
+    let pixel_count = width * height;
+    let mut pixels = Vec::with_capacity(pixel_count);
+
+    for &(count, color) in runs {
+        let remaining = pixel_count - pixels.len();
+        pixels.extend(core::iter::repeat_n(color, (count as usize).min(remaining)));
+    }
+    // Pad short tables with transparent pixels
+    pixels.resize(pixel_count, 0x00000000);
+
+    Bitmap { width, height, pixels }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn encode_then_decode_round_trips() {
+        let pixels = vec![0, 0, 0, 0xFFFFFFFF, 0xFFFFFFFF, 0, 0xFF123456, 0xFF123456, 0xFF123456];
+        let bitmap = Bitmap { width: 3, height: 3, pixels: pixels.clone() };
+
+        let runs = encode_icon(&bitmap);
+        assert_eq!(runs, [(3, 0), (2, 0xFFFFFFFF), (1, 0), (3, 0xFF123456)]);
+        assert_eq!(decode_icon(3, 3, &runs).pixels, pixels);
+    }
+
+    #[test]
+    fn decode_pads_short_tables_and_truncates_long_ones() {
+        assert_eq!(decode_icon(2, 2, &[(1, 0xFFFFFFFF)]).pixels, [0xFFFFFFFF, 0, 0, 0]);
+        assert_eq!(decode_icon(2, 1, &[(5, 0xFFFFFFFF)]).pixels, [0xFFFFFFFF; 2]);
+    }
+}
@@ -0,0 +1,26 @@
+use spin::Lazy;
+use crate::drivers::display::bitmap::Bitmap;
+
+// A 24x24 red/green gradient, stored as a regular 24-bit BMP file
+const SWATCH_BMP: &[u8] = include_bytes!("swatch.bmp");
+
+static SWATCH_ICON: Lazy<Bitmap> =
+    Lazy::new(|| Bitmap::from_bmp(SWATCH_BMP).expect("embedded swatch is a valid BMP"));
+
+pub fn get_swatch_icon() -> &'static Bitmap {
+    &SWATCH_ICON
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swatch_is_a_gradient_from_its_top_left_corner() {
+        let icon = get_swatch_icon();
+        assert_eq!((icon.width, icon.height), (24, 24));
+        assert_eq!(icon.pixels[0], 0xFF000080);
+        assert_eq!(icon.pixels[23], 0xFFFF0080);
+        assert_eq!(icon.pixels[24 * 24 - 1], 0xFFFFFF80);
+    }
+}
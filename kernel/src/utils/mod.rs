@@ -1 +1,2 @@
-pub mod icons;
\ No newline at end of file
+pub mod icons;
+pub mod random;
@@ -0,0 +1,85 @@
+use spin::Mutex;
+use crate::drivers::timer;
+
+// Used when a zero seed is given, since xorshift never leaves the all-zero state
+const FALLBACK_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// xorshift64 state. Not suitable for anything security related.
+static STATE: Mutex<Option<u64>> = Mutex::new(None);
+
+pub fn seed(value: u64) {
+    *STATE.lock() = Some(if value == 0 { FALLBACK_SEED } else { value });
+}
+
+pub fn next_u64() -> u64 {
+    let mut state = STATE.lock();
+
+    // Seeded lazily from the tick counter, so the time until the first use varies the sequence
+    let mut x = state.unwrap_or_else(|| timer::ticks().wrapping_mul(FALLBACK_SEED) | 1);
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+
+    *state = Some(x);
+    x
+}
+
+// Uniform enough for small ranges; both bounds are inclusive
+pub fn range(min: u64, max: u64) -> u64 {
+    let (min, max) = if min <= max { (min, max) } else { (max, min) };
+    match (max - min).checked_add(1) {
+        Some(span) => min + next_u64() % span,
+        None => next_u64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The generator state is global, so tests take turns seeding it
+    static STATE_USERS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn sequence(seed_value: u64) -> [u64; 8] {
+        seed(seed_value);
+        core::array::from_fn(|_| next_u64())
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_sequence() {
+        let _state = STATE_USERS.lock().unwrap();
+        assert_eq!(sequence(42), sequence(42));
+        assert_ne!(sequence(42), sequence(43));
+    }
+
+    #[test]
+    fn zero_seed_falls_back_instead_of_sticking_at_zero() {
+        let _state = STATE_USERS.lock().unwrap();
+        assert_eq!(sequence(0), sequence(FALLBACK_SEED));
+        assert!(sequence(0).iter().all(|&value| value != 0));
+    }
+
+    #[test]
+    fn range_stays_within_inclusive_bounds() {
+        let _state = STATE_USERS.lock().unwrap();
+        seed(7);
+        let mut seen = [false; 6];
+        for _ in 0..1_000 {
+            let value = range(10, 15);
+            assert!((10..=15).contains(&value));
+            seen[(value - 10) as usize] = true;
+        }
+        assert!(seen.iter().all(|&hit| hit), "both bounds should be reachable");
+
+        assert_eq!(range(5, 5), 5);
+        assert!((3..=9).contains(&range(9, 3)));
+    }
+
+    #[test]
+    fn full_range_does_not_overflow() {
+        let _state = STATE_USERS.lock().unwrap();
+        let expected = sequence(7)[0];
+        seed(7);
+        assert_eq!(range(0, u64::MAX), expected);
+    }
+}
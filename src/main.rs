@@ -10,6 +10,7 @@ fn main() {
     let status = Command::new("qemu-system-x86_64")
         .args([
             "-drive", &format!("format=raw,file={}", bios_path),
+            "-serial", "stdio",
         ])
         .status()
         .expect("Failed to start QEMU. Make sure qemu-system-x86_64 is installed and in your PATH.");